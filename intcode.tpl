@@ -0,0 +1,43 @@
+use aoc2019::cpu::{Cpu, CpuState};
+use aoc2019::{dispatch, Result};
+use failure::bail;
+
+fn main() -> Result<()> {
+    dispatch(&part1, &part2)
+}
+
+fn run(program: &str, inputs: &[i64]) -> Result<Vec<i64>> {
+    let mut cpu = Cpu::from_str(program)?;
+    for &value in inputs {
+        cpu.enqueue_input(value);
+    }
+    let mut outputs = vec![];
+    loop {
+        match cpu.run()? {
+            CpuState::Output(value) => outputs.push(value),
+            CpuState::NeedsInput => bail!("out of input"),
+            CpuState::Halted => break Ok(outputs),
+        }
+    }
+}
+
+fn part1(input: &str) -> Result<i64> {
+    let _outputs = run(input, &[])?;
+    Ok(0)
+}
+
+fn part2(input: &str) -> Result<i64> {
+    let _outputs = run(input, &[])?;
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        assert_eq!(run("3,5,4,5,99,0", &[7])?, vec![7]);
+        Ok(())
+    }
+}
@@ -0,0 +1,376 @@
+use crate::bitset::BitSet;
+use crate::coor::{Bounds, Coor};
+use crate::cycle::{self, Cycle};
+use failure::{bail, Error};
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::iter;
+use std::str::FromStr;
+
+/// Which cells count as neighbours on a flat grid.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Neighbourhood {
+    /// The 4 orthogonal cells.
+    VonNeumann,
+    /// The 8 surrounding cells.
+    Moore,
+}
+
+impl Neighbourhood {
+    fn of(self, pos: Coor) -> Vec<Coor> {
+        match self {
+            Neighbourhood::VonNeumann => pos.neighbours4().collect(),
+            Neighbourhood::Moore => pos.neighbours8().collect(),
+        }
+    }
+}
+
+/// How cells are laid out and connected. Neighbours must be symmetric: if `b`
+/// is a neighbour of `a`, `a` is one of `b`.
+pub trait Topology {
+    type Pos: Ord + Hash + Clone;
+
+    fn neighbours(&self, pos: &Self::Pos) -> Vec<Self::Pos>;
+
+    /// Whether `pos` is a cell at all.
+    fn contains(&self, _pos: &Self::Pos) -> bool {
+        true
+    }
+}
+
+/// A flat grid, with nothing outside `bounds`.
+pub struct Bounded {
+    pub bounds: Bounds,
+    pub neighbourhood: Neighbourhood,
+}
+
+impl Topology for Bounded {
+    type Pos = Coor;
+
+    fn neighbours(&self, &pos: &Coor) -> Vec<Coor> {
+        let mut neighbours = self.neighbourhood.of(pos);
+        neighbours.retain(|&n| self.bounds.contains(n));
+        neighbours
+    }
+
+    fn contains(&self, &pos: &Coor) -> bool {
+        self.bounds.contains(pos)
+    }
+}
+
+/// A flat grid whose edges wrap around.
+pub struct Toroidal {
+    pub bounds: Bounds,
+    pub neighbourhood: Neighbourhood,
+}
+
+impl Topology for Toroidal {
+    type Pos = Coor;
+
+    fn neighbours(&self, &pos: &Coor) -> Vec<Coor> {
+        let (min, width, height) = (self.bounds.min, self.bounds.width(), self.bounds.height());
+        self.neighbourhood
+            .of(pos)
+            .into_iter()
+            .map(|n| {
+                Coor::new(
+                    min.x + (n.x - min.x).rem_euclid(width),
+                    min.y + (n.y - min.y).rem_euclid(height),
+                )
+            })
+            .collect()
+    }
+
+    fn contains(&self, &pos: &Coor) -> bool {
+        self.bounds.contains(pos)
+    }
+}
+
+/// An unbounded flat grid.
+pub struct Infinite {
+    pub neighbourhood: Neighbourhood,
+}
+
+impl Topology for Infinite {
+    type Pos = Coor;
+
+    fn neighbours(&self, &pos: &Coor) -> Vec<Coor> {
+        self.neighbourhood.of(pos)
+    }
+}
+
+/// `size` by `size` grids nested in each other's centre cell, which isn't a
+/// cell itself. Positions are `(coor, depth)`, the grid in the centre of
+/// depth `d` being at depth `d + 1`.
+pub struct Recursive {
+    pub size: i64,
+}
+
+impl Recursive {
+    fn centre(&self) -> Coor {
+        Coor::new(self.size / 2, self.size / 2)
+    }
+}
+
+impl Topology for Recursive {
+    type Pos = (Coor, i64);
+
+    fn neighbours(&self, &(pos, depth): &(Coor, i64)) -> Vec<(Coor, i64)> {
+        let centre = self.centre();
+        let last = self.size - 1;
+        let mut neighbours = vec![];
+        for next in pos.neighbours4() {
+            let offset = next - pos;
+            if next == centre {
+                // the edge of the inner grid facing us
+                neighbours.extend((0..self.size).map(|i| {
+                    let inner = match (offset.x, offset.y) {
+                        (1, _) => Coor::new(0, i),
+                        (-1, _) => Coor::new(last, i),
+                        (_, 1) => Coor::new(i, 0),
+                        _ => Coor::new(i, last),
+                    };
+                    (inner, depth + 1)
+                }));
+            } else if next.x < 0 || next.y < 0 || next.x > last || next.y > last {
+                neighbours.push((centre + offset, depth - 1));
+            } else {
+                neighbours.push((next, depth));
+            }
+        }
+        neighbours
+    }
+
+    fn contains(&self, &(pos, _): &(Coor, i64)) -> bool {
+        pos != self.centre() && pos.x >= 0 && pos.y >= 0 && pos.x < self.size && pos.y < self.size
+    }
+}
+
+/// Live neighbour counts for which a dead cell comes alive, and for which a
+/// live one stays alive.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rule {
+    birth: BitSet<u32>,
+    survival: BitSet<u32>,
+}
+
+impl Rule {
+    /// Birth on 0 neighbours isn't supported: only neighbours of live cells
+    /// are ever considered.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        Rule {
+            birth: birth.iter().cloned().collect(),
+            survival: survival.iter().cloned().collect(),
+        }
+    }
+
+    /// Conway's game of life, `B3/S23`.
+    pub fn life() -> Self {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    pub fn next(&self, alive: bool, neighbours: usize) -> bool {
+        if alive {
+            self.survival.contains(neighbours)
+        } else {
+            self.birth.contains(neighbours)
+        }
+    }
+}
+
+/// Parses the `B3/S23` notation.
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        let counts = |part: &str, prefix: char| -> Result<Vec<usize>, Error> {
+            let mut chars = part.chars();
+            if chars.next().map(|c| c.to_ascii_uppercase()) != Some(prefix) {
+                bail!("expected {} in rule {:?}", prefix, value);
+            }
+            chars
+                .map(|c| match c.to_digit(10) {
+                    Some(d) => Ok(d as usize),
+                    None => bail!("bad count {:?} in rule {:?}", c, value),
+                })
+                .collect()
+        };
+        let mut parts = value.split('/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(birth), Some(survival), None) => {
+                Ok(Rule::new(&counts(birth, 'B')?, &counts(survival, 'S')?))
+            }
+            _ => bail!("can't parse rule {:?}", value),
+        }
+    }
+}
+
+/// A two state cellular automaton, keeping track of its live cells only.
+pub struct Automaton<T: Topology> {
+    topology: T,
+    rule: Rule,
+    live: BTreeSet<T::Pos>,
+}
+
+impl<T: Topology> Automaton<T> {
+    /// Positions outside the topology are dropped.
+    pub fn new<I: IntoIterator<Item = T::Pos>>(topology: T, rule: Rule, live: I) -> Self {
+        let live = live
+            .into_iter()
+            .filter(|pos| topology.contains(pos))
+            .collect();
+        Automaton {
+            topology,
+            rule,
+            live,
+        }
+    }
+
+    pub fn live(&self) -> &BTreeSet<T::Pos> {
+        &self.live
+    }
+
+    pub fn population(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn live_neighbours(&self, pos: &T::Pos) -> usize {
+        self.topology
+            .neighbours(pos)
+            .iter()
+            .filter(|n| self.live.contains(n))
+            .count()
+    }
+
+    fn next(&self, live: &BTreeSet<T::Pos>) -> BTreeSet<T::Pos> {
+        let mut counts: HashMap<&T::Pos, usize> = live.iter().map(|pos| (pos, 0)).collect();
+        let neighbours: Vec<_> = live.iter().map(|p| self.topology.neighbours(p)).collect();
+        for n in neighbours.iter().flatten() {
+            *counts.entry(n).or_insert(0) += 1;
+        }
+        counts
+            .into_iter()
+            .filter(|&(pos, count)| self.rule.next(live.contains(pos), count))
+            .map(|(pos, _)| pos.clone())
+            .collect()
+    }
+
+    pub fn step(&mut self) {
+        self.live = self.next(&self.live);
+    }
+
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// The first set of live cells seen before, and where the cycle is,
+    /// counting the current generation as 0. Never returns if the pattern
+    /// keeps growing.
+    pub fn find_cycle(&self) -> (Cycle, BTreeSet<T::Pos>) {
+        let generations = iter::successors(Some(self.live.clone()), |live| Some(self.next(live)));
+        cycle::first_repeat(generations).expect("generations never end")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: i64) -> Bounds {
+        Bounds::new(Coor::default(), Coor::new(size - 1, size - 1))
+    }
+
+    fn coors(cells: &[(i64, i64)]) -> Vec<Coor> {
+        cells.iter().map(|&(x, y)| Coor::new(x, y)).collect()
+    }
+
+    #[test]
+    fn test_rule() -> crate::Result<()> {
+        assert_eq!("B3/S23".parse::<Rule>()?, Rule::life());
+        assert_eq!("b12/s1".parse::<Rule>()?, Rule::new(&[1, 2], &[1]));
+        assert!("B3".parse::<Rule>().is_err());
+        assert!("B3/Sx".parse::<Rule>().is_err());
+        let life = Rule::life();
+        assert!(life.next(false, 3) && !life.next(false, 2));
+        assert!(life.next(true, 2) && !life.next(true, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blinker() {
+        let topology = Infinite {
+            neighbourhood: Neighbourhood::Moore,
+        };
+        let mut life = Automaton::new(topology, Rule::life(), coors(&[(0, 1), (1, 1), (2, 1)]));
+        life.step();
+        assert_eq!(
+            life.live().iter().cloned().collect::<Vec<_>>(),
+            coors(&[(1, 0), (1, 1), (1, 2)])
+        );
+        life.run(3);
+        assert_eq!(life.population(), 3);
+        assert!(life.live().contains(&Coor::new(0, 1)));
+        let (cycle, _) = life.find_cycle();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_flat_edges() {
+        // a glider comes back after crossing the whole torus
+        let glider = coors(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let topology = Toroidal {
+            bounds: square(5),
+            neighbourhood: Neighbourhood::Moore,
+        };
+        let mut torus = Automaton::new(topology, Rule::life(), glider.clone());
+        torus.run(20);
+        assert_eq!(torus.live().iter().cloned().collect::<Vec<_>>(), {
+            let mut sorted = glider.clone();
+            sorted.sort();
+            sorted
+        });
+
+        // while a bounded grid stops it in a corner
+        let topology = Bounded {
+            bounds: square(5),
+            neighbourhood: Neighbourhood::Moore,
+        };
+        let mut bounded = Automaton::new(topology, Rule::life(), glider);
+        bounded.run(20);
+        assert_eq!(
+            bounded.live().iter().cloned().collect::<Vec<_>>(),
+            coors(&[(3, 3), (4, 3), (3, 4), (4, 4)])
+        );
+        assert_eq!(bounded.live_neighbours(&Coor::new(0, 0)), 0);
+    }
+
+    #[test]
+    fn test_recursive_neighbours() {
+        let recursive = Recursive { size: 5 };
+        let count = |pos: (i64, i64), depth| {
+            recursive
+                .neighbours(&(Coor::new(pos.0, pos.1), depth))
+                .len()
+        };
+        assert_eq!(count((3, 3), 0), 4);
+        assert_eq!(count((0, 0), 0), 4);
+        assert_eq!(count((3, 2), 0), 8);
+        assert_eq!(count((1, 2), 0), 8);
+        assert!(recursive
+            .neighbours(&(Coor::new(0, 0), 0))
+            .contains(&(Coor::new(1, 2), -1)));
+        assert!(recursive
+            .neighbours(&(Coor::new(2, 1), 0))
+            .contains(&(Coor::new(4, 0), 1)));
+        assert!(!recursive.contains(&(Coor::new(2, 2), 3)));
+        assert!(!recursive.contains(&(Coor::new(5, 0), 0)));
+    }
+}
@@ -0,0 +1,159 @@
+use aoc2019::runner::{run_parallel, Task};
+use aoc2019::scaffold::{new_day, template_path};
+use aoc2019::Result;
+use clap::{App, AppSettings, Arg, SubCommand};
+use failure::bail;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+fn day_binary(day: u32) -> Result<PathBuf> {
+    let exe = env::current_exe()?;
+    Ok(exe.with_file_name(format!("day{:02}{}", day, env::consts::EXE_SUFFIX)))
+}
+
+fn run_day(binary: &Path, part: u8, cache_dir: &str) -> Result<String> {
+    let output = Command::new(binary)
+        .arg("-p")
+        .arg(part.to_string())
+        .arg("--cache-dir")
+        .arg(cache_dir)
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("");
+        bail!("{} ({})", message, output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Runs both parts of `days` (all built days if empty), one task per part.
+fn run_days(days: Vec<u32>, jobs: usize, cache_dir: &str) -> Result<bool> {
+    let days = if days.is_empty() {
+        (1..=25)
+            .filter(|&day| day_binary(day).map(|b| b.exists()).unwrap_or(false))
+            .collect()
+    } else {
+        days
+    };
+    let mut tasks = vec![];
+    for day in days {
+        let binary = day_binary(day)?;
+        for part in 1..=2 {
+            let binary = binary.clone();
+            let cache_dir = cache_dir.to_string();
+            tasks.push(Task::new(&format!("day{:02} p{}", day, part), move || {
+                if !binary.exists() {
+                    bail!("{} not built", binary.display());
+                }
+                run_day(&binary, part, &cache_dir)
+            }));
+        }
+    }
+
+    let live = io::stderr().is_terminal();
+    let results = run_parallel(tasks, jobs, |progress| {
+        if live {
+            eprint!("\r\x1b[K{}", progress);
+            let _ = io::stderr().flush();
+        }
+    });
+    if live {
+        eprint!("\r\x1b[K");
+    }
+
+    let mut ok = true;
+    for result in results {
+        let elapsed = result.elapsed.as_secs_f64();
+        match result.result {
+            Ok(answer) => println!("{}: {} ({:.2}s)", result.label, answer, elapsed),
+            Err(e) => {
+                ok = false;
+                println!("{}: FAILED {} ({:.2}s)", result.label, e, elapsed)
+            }
+        }
+    }
+    Ok(ok)
+}
+
+fn main() -> Result<()> {
+    let matches = App::new("aoc")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("root")
+                .long("root")
+                .help("Repository root")
+                .takes_value(true)
+                .default_value("."),
+        )
+        .subcommand(
+            SubCommand::with_name("new")
+                .about("Creates src/bin/dayNN.rs from a template, plus empty input and answers")
+                .arg(
+                    Arg::with_name("template")
+                        .short("t")
+                        .long("template")
+                        .help("Template name (e.g. `intcode` for intcode.tpl) or path")
+                        .takes_value(true),
+                )
+                .arg(Arg::with_name("day").required(true).index(1)),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Runs both parts of the given days (default all) in parallel")
+                .arg(
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .help("Number of worker threads (default: available cores)")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("cache-dir")
+                        .long("cache-dir")
+                        .help("Passed on to each day")
+                        .takes_value(true)
+                        .env("AOC_CACHE_DIR")
+                        .default_value("."),
+                )
+                .arg(Arg::with_name("days").multiple(true).index(1)),
+        )
+        .get_matches();
+
+    let root = Path::new(matches.value_of("root").expect("root has a default"));
+    match matches.subcommand() {
+        ("new", Some(matches)) => {
+            let day = matches.value_of("day").expect("day is required").parse()?;
+            let template = template_path(root, matches.value_of("template"));
+            for path in new_day(root, day, &template)? {
+                println!("created {}", path.display());
+            }
+        }
+        ("run", Some(matches)) => {
+            let days = matches
+                .values_of("days")
+                .map(|values| values.map(str::parse).collect())
+                .unwrap_or_else(|| Ok(vec![]))?;
+            let jobs = match matches.value_of("jobs") {
+                Some(jobs) => jobs.parse()?,
+                None => thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            let cache_dir = matches
+                .value_of("cache-dir")
+                .expect("cache-dir has a default");
+            if !run_days(days, jobs, cache_dir)? {
+                std::process::exit(1);
+            }
+        }
+        (command, _) => bail!("unknown command: {}", command),
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use aoc2019::parse::numbers;
 use aoc2019::{dispatch, Result};
 
 fn main() -> Result<()> {
@@ -5,11 +6,7 @@ fn main() -> Result<()> {
 }
 
 fn part1(input: &str) -> Result<i32> {
-    Ok(input
-        .split('\n')
-        .filter_map(|x| x.parse::<i32>().ok())
-        .map(|x| x / 3 - 2)
-        .sum())
+    Ok(numbers::<i32>(input)?.into_iter().map(|x| x / 3 - 2).sum())
 }
 
 fn recurse_fuel(mass: i32) -> i32 {
@@ -23,11 +20,7 @@ fn recurse_fuel(mass: i32) -> i32 {
 }
 
 fn part2(input: &str) -> Result<i32> {
-    Ok(input
-        .split('\n')
-        .filter_map(|x| x.parse::<i32>().ok())
-        .map(recurse_fuel)
-        .sum())
+    Ok(numbers(input)?.into_iter().map(recurse_fuel).sum())
 }
 
 #[cfg(test)]
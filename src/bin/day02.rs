@@ -8,7 +8,7 @@ fn main() -> Result<()> {
 }
 
 fn part1(input: &str) -> Result<i64> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     set_memory(&mut cpu, 1, 12);
     set_memory(&mut cpu, 2, 2);
     assert_eq!(cpu.run()?, CpuState::Halted);
@@ -18,7 +18,7 @@ fn part1(input: &str) -> Result<i64> {
 fn part2(input: &str) -> Result<i64> {
     for noun in 0..=99 {
         for verb in 0..=99 {
-            let mut cpu = Cpu::from_str(input);
+            let mut cpu = Cpu::from_str(input)?;
             set_memory(&mut cpu, 1, noun);
             set_memory(&mut cpu, 2, verb);
             assert_eq!(cpu.run()?, CpuState::Halted);
@@ -35,7 +35,7 @@ mod tests {
     use super::*;
 
     fn run(input: &str) -> Result<i64> {
-        let mut cpu = Cpu::from_str(input);
+        let mut cpu = Cpu::from_str(input)?;
         assert_eq!(cpu.run()?, CpuState::Halted);
         Ok(read_memory(&cpu, 0))
     }
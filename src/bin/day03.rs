@@ -1,147 +1,88 @@
-#![feature(generators, generator_trait)]
-
+use aoc2019::coor::{Coor, Direction};
+use aoc2019::parse::{parse_lines, split_with, ParseError};
 use aoc2019::{dispatch, Result};
-use failure::{bail, Error};
-use std::cmp::Ordering;
+use failure::{err_msg, Error};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
-use std::ops::Add;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl TryFrom<&str> for Direction {
-    type Error = Error;
-
-    fn try_from(value: &str) -> Result<Self> {
-        use Direction::*;
-
-        match &value[..1] {
-            "U" => Ok(Up),
-            "D" => Ok(Down),
-            "L" => Ok(Left),
-            "R" => Ok(Right),
-            _ => bail!("Invalid direction"),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, Hash)]
-struct Coor {
-    x: i32,
-    y: i32,
-}
-
-impl Coor {
-    fn new(x: i32, y: i32) -> Self {
-        Coor { x, y }
-    }
-
-    fn distance(&self) -> i32 {
-        self.x.abs() + self.y.abs()
-    }
-}
-
-impl Add for Coor {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Coor::new(self.x + other.x, self.y + other.y)
-    }
-}
-
-impl Ord for Coor {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.distance().cmp(&other.distance())
-    }
-}
-impl PartialOrd for Coor {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[derive(Debug, PartialEq, Eq)]
 struct Vector {
     direction: Direction,
-    length: i32,
-}
-
-impl Vector {
-    fn as_coor_magnitude_pair(&self) -> (Coor, i32) {
-        // fn as_coor(&self) -> Coor {
-        use Direction::*;
-        let coor = match &self.direction {
-            Up => Coor::new(0, 1),
-            Down => Coor::new(0, -1),
-            Left => Coor::new(-1, 0),
-            Right => Coor::new(1, 0),
-        };
-        (coor, self.length)
-    }
+    length: u32,
 }
 
 impl TryFrom<&str> for Vector {
     type Error = Error;
 
     fn try_from(value: &str) -> Result<Self> {
-        Ok(Vector {
-            direction: Direction::try_from(value)?,
-            length: i32::from_str_radix(&value[1..], 10)?,
-        })
+        let first = value
+            .chars()
+            .next()
+            .ok_or_else(|| ParseError::new(1, 1, "empty step"))?;
+        let direction =
+            Direction::try_from(first).map_err(|e| ParseError::new(1, 1, &e.to_string()))?;
+        let length = &value[first.len_utf8()..];
+        let length = length.parse().map_err(|e| {
+            let message = format!("invalid length {:?}: {}", length, e);
+            ParseError::new(1, first.len_utf8() + 1, &message)
+        })?;
+        Ok(Vector { direction, length })
+    }
+}
+
+/// Two wires, one per line.
+fn parse(input: &str) -> Result<(Vec<Vector>, Vec<Vector>)> {
+    let mut wires = parse_lines(input, |line| {
+        split_with(line, ",", |token| Vector::try_from(token))
+    })?;
+    if wires.len() != 2 {
+        let line = wires.len().min(2) + 1;
+        return Err(ParseError::new(line, 1, "expected two wires").into());
     }
+    let wire2 = wires.remove(1);
+    Ok((wires.remove(0), wire2))
 }
 
-fn wire_positions(input: &str) -> HashMap<Coor, i32> {
+fn wire_positions(wire: &[Vector]) -> HashMap<Coor, i32> {
     let mut pos = Coor::default();
     let mut steps = 0;
     let mut positions = HashMap::new();
 
-    for vector in input.split(',').filter_map(|x| Vector::try_from(x).ok()) {
-        let (direction, magnitude) = vector.as_coor_magnitude_pair();
-        for _ in 0..magnitude {
+    for vector in wire {
+        for _ in 0..vector.length {
             steps += 1;
-            pos = pos + direction;
+            pos += vector.direction.offset();
             positions.insert(pos, steps);
         }
     }
     positions
 }
 
-fn part1(input: &str) -> Result<i32> {
-    let mut wires = input.split('\n');
-    let wire1 = wires.next().unwrap();
-    let wire2 = wires.next().unwrap();
+fn part1(input: &str) -> Result<i64> {
+    let (wire1, wire2) = parse(input)?;
 
-    let steps1 = wire_positions(wire1);
-    let steps2 = wire_positions(wire2);
+    let steps1 = wire_positions(&wire1);
+    let steps2 = wire_positions(&wire2);
     let positions1: HashSet<_> = steps1.keys().collect();
     let positions2: HashSet<_> = steps2.keys().collect();
 
     let intersections = positions1.intersection(&positions2);
     // dbg!(&intersections);
-    let mut intersections: Vec<_> = intersections.cloned().collect();
-    intersections.sort();
-    let smallest = intersections[0];
-    Ok(smallest.x + smallest.y)
+    intersections
+        .map(|c| c.manhattan(Coor::default()))
+        .min()
+        .ok_or_else(|| err_msg("the wires don't cross"))
 }
 
 fn part2(input: &str) -> Result<i32> {
-    let mut wires = input.split('\n');
-    let wire1 = wires.next().unwrap();
-    let wire2 = wires.next().unwrap();
+    let (wire1, wire2) = parse(input)?;
 
-    let steps1 = wire_positions(wire1);
-    let steps2 = wire_positions(wire2);
+    let steps1 = wire_positions(&wire1);
+    let steps2 = wire_positions(&wire2);
     let positions1: HashSet<_> = steps1.keys().collect();
     let positions2: HashSet<_> = steps2.keys().collect();
 
@@ -154,9 +95,10 @@ fn part2(input: &str) -> Result<i32> {
     }
     // dbg!(&intersections);
     // let mut intersections: Vec<_> = intersections.cloned().collect();
-    intersection_steps.sort();
-    let smallest = intersection_steps[0];
-    Ok(smallest)
+    intersection_steps
+        .into_iter()
+        .min()
+        .ok_or_else(|| err_msg("the wires don't cross"))
 }
 
 #[cfg(test)]
@@ -165,7 +107,42 @@ mod tests {
 
     #[test]
     fn test_direction() -> Result<()> {
-        assert_eq!(Direction::try_from("U21")?, Direction::Up);
+        assert_eq!(Vector::try_from("U21")?.direction, Direction::Up);
+        assert_eq!(
+            Vector::try_from("→4")?,
+            Vector {
+                direction: Direction::Right,
+                length: 4
+            }
+        );
+        for (step, column) in &[("", 1), ("X4", 1), ("→x", 4), ("R-3", 2)] {
+            let e = Vector::try_from(*step)
+                .expect_err("should fail")
+                .downcast::<ParseError>()
+                .expect("ParseError");
+            assert_eq!(e.column, *column);
+        }
+        assert_eq!(part1("→8,↑5,←5,↓3\nU7,R6,D4,L4")?, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let (wire1, wire2) = parse("R8,U5\r\nU7\n")?;
+        assert_eq!((wire1.len(), wire2.len()), (2, 1));
+        for (input, position) in &[
+            ("R8,U5,L5,D3\nU7,R6,D4,Lx4", (2, 11)),
+            ("R8,U5,L5,D3\nU7,,D4", (2, 4)),
+            ("R8,U5,L5,D3", (2, 1)),
+            ("R8\nU5\nL5", (3, 1)),
+        ] {
+            let e = parse(input)
+                .expect_err("should fail")
+                .downcast::<ParseError>()
+                .expect("ParseError");
+            assert_eq!((e.line, e.column), *position);
+        }
+        assert!(part1("R8\nU8").is_err());
         Ok(())
     }
 
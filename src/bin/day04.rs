@@ -1,6 +1,6 @@
-#![feature(slice_patterns)]
-
+use aoc2019::parse::separated;
 use aoc2019::{dispatch, Result};
+use failure::bail;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -60,10 +60,10 @@ fn is_password2(number: i32) -> bool {
 }
 
 fn count_in_range(input: &str, checker: fn(i32) -> bool) -> Result<i32> {
-    let start_end: Vec<_> = input
-        .split('-')
-        .filter_map(|x| x.parse::<i32>().ok())
-        .collect();
+    let start_end: Vec<i32> = separated(input.trim_end(), '-')?;
+    if start_end.len() != 2 {
+        bail!("expected a range like 123-456, got {:?}", input);
+    }
     Ok((start_end[0]..=start_end[1])
         .filter(|&x| checker(x))
         .count() as i32)
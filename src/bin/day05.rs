@@ -6,7 +6,7 @@ fn main() -> Result<()> {
 }
 
 fn calculate(program_str: &str, input_value: i64) -> Result<i64> {
-    let mut cpu = Cpu::from_str(program_str);
+    let mut cpu = Cpu::from_str(program_str)?;
     cpu.enqueue_input(input_value);
     cpu.run().map(|res| match res {
         CpuState::Output(output) => output,
@@ -15,7 +15,7 @@ fn calculate(program_str: &str, input_value: i64) -> Result<i64> {
 }
 
 fn part1(input: &str) -> Result<i64> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     cpu.enqueue_input(1);
     let mut outputs = vec![];
     loop {
@@ -1,5 +1,8 @@
+use aoc2019::parse::{parse_lines, ParseError};
+use aoc2019::search::bfs;
 use aoc2019::{dispatch, Result};
-use std::collections::{HashMap, HashSet, VecDeque};
+use failure::{err_msg, format_err};
+use std::collections::{HashMap, HashSet};
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -12,69 +15,82 @@ fn count(parents: &HashMap<&str, &str>, body: &str) -> i32 {
     }
 }
 
-fn parse(input: &str) -> HashMap<&str, &str> {
-    let orbits: Vec<_> = input
-        .split('\n')
-        .map(|l| {
-            let mut pair = l.split(')');
-            (pair.next().unwrap(), pair.next().unwrap())
-        })
-        .collect();
+/// `A)B`, B orbiting A.
+fn orbit(line: &str) -> Result<(&str, &str)> {
+    match line.split(')').collect::<Vec<_>>()[..] {
+        [parent, child] if !parent.is_empty() && !child.is_empty() => Ok((parent, child)),
+        _ => Err(ParseError::new(1, 1, &format!("expected A)B, got {:?}", line)).into()),
+    }
+}
 
+fn parse(input: &str) -> Result<HashMap<&str, &str>> {
+    let orbits = parse_lines(input, orbit)?;
     let mut parents = HashMap::new();
-    for (parent, child) in orbits {
-        if let Some(_other) = parents.get(child) {
-            panic!("multiple 1");
+    for (idx, (parent, child)) in orbits.into_iter().enumerate() {
+        if parents.insert(child, parent).is_some() {
+            let message = format!("{} already orbits something", child);
+            return Err(ParseError::new(idx + 1, 1, &message).into());
         }
-        parents.insert(child, parent);
     }
-    parents
+    Ok(parents)
 }
 
 fn part1(input: &str) -> Result<i32> {
-    let parents = parse(input);
+    let parents = parse(input)?;
     let sum = parents.keys().map(|&body| count(&parents, body)).sum();
     Ok(sum)
 }
 
 fn part2(input: &str) -> Result<i32> {
-    let parents = parse(input);
+    let parents = parse(input)?;
     let mut children_map: HashMap<&str, Vec<&str>> = HashMap::new();
     for (key, value) in parents.iter() {
         children_map.entry(&value).or_insert(vec![]).push(key);
     }
-    let start = parents.get("YOU").unwrap();
-    let end = parents.get("SAN").unwrap();
+    let orbited = |body| {
+        parents
+            .get(body)
+            .ok_or_else(|| format_err!("{} isn't orbiting anything", body))
+    };
+    let start = orbited("YOU")?;
+    let end = orbited("SAN")?;
 
-    let mut queue = VecDeque::new();
-    let mut seen = HashSet::new();
-    queue.push_back((start, 0));
-    seen.insert(start);
-    while let Some((next, distance)) = queue.pop_front() {
-        seen.insert(next);
-        if next == end {
-            return Ok(distance);
-        }
-        if let Some(parent) = parents.get(next) {
-            if !seen.contains(parent) {
-                queue.push_back((parent, distance + 1));
-            }
-        }
-        if let Some(chilren) = children_map.get(next) {
-            for child in chilren.iter() {
-                if !seen.contains(child) {
-                    queue.push_back((child, distance + 1));
-                }
-            }
-        }
-    }
-    Ok(0)
+    bfs(
+        *start,
+        HashSet::new(),
+        |&body| {
+            parents
+                .get(body)
+                .into_iter()
+                .chain(children_map.get(body).into_iter().flatten())
+                .cloned()
+                .collect::<Vec<_>>()
+        },
+        |body| body == end,
+    )
+    .cost()
+    .map(|distance| distance as i32)
+    .ok_or_else(|| err_msg("no path to santa"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse() -> Result<()> {
+        assert_eq!(parse("COM)B\r\nB)C\n")?.get("C"), Some(&"B"));
+        for (input, line) in &[("COM)B\nBC", 2), ("COM)B\n)C", 2), ("A)B\nC)B\n", 2)] {
+            let e = parse(input)
+                .expect_err("should fail")
+                .downcast::<ParseError>()
+                .expect("ParseError");
+            assert_eq!(e.line, *line);
+        }
+        assert!(part2("COM)B\nB)YOU").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_part1() -> Result<()> {
         assert_eq!(
@@ -7,7 +7,7 @@ fn main() -> Result<()> {
 }
 
 fn calculate(program_str: &str, input_values: &[i64]) -> Result<i64> {
-    let mut cpu = Cpu::from_str(program_str);
+    let mut cpu = Cpu::from_str(program_str)?;
     for input_value in input_values.iter() {
         cpu.enqueue_input(*input_value);
     }
@@ -43,11 +43,11 @@ fn part2(input: &str) -> Result<i64> {
 
     loop {
         let mut cpus = vec![
-            Cpu::from_str(input),
-            Cpu::from_str(input),
-            Cpu::from_str(input),
-            Cpu::from_str(input),
-            Cpu::from_str(input),
+            Cpu::from_str(input)?,
+            Cpu::from_str(input)?,
+            Cpu::from_str(input)?,
+            Cpu::from_str(input)?,
+            Cpu::from_str(input)?,
         ];
 
         for (index, &phase) in phases.iter().enumerate() {
@@ -1,78 +1,238 @@
-use aoc2019::{dispatch, Result};
-use failure::err_msg;
-use std::collections::HashMap;
-use std::io::Write;
-use std::str;
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::bail;
+use std::iter;
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(
+        &part1,
+        &part2,
+        &[("flatten", &flatten), ("pbm", &pbm), ("pgm", &pgm)],
+    )
 }
 
-fn checksum(input: &str, size: usize) -> Result<i32> {
-    let mut fewest0 = size;
-    let mut best_count = 0;
-    for layer in input.chars().collect::<Vec<_>>().chunks(size) {
-        let mut counts = HashMap::new();
-        for digit in layer {
-            *counts.entry(digit).or_insert(0) += 1;
-        }
-        let get_count = |d| *counts.get(&d).unwrap_or(&0);
-        let zeros = get_count('0');
-        if zeros < fewest0 {
-            fewest0 = zeros;
-            best_count = (get_count('1') * get_count('2')) as i32;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Pixel {
+    Black,
+    White,
+    Transparent,
+}
+
+impl Pixel {
+    fn from_digit(digit: u8) -> Result<Self> {
+        use Pixel::*;
+        match digit {
+            0 => Ok(Black),
+            1 => Ok(White),
+            2 => Ok(Transparent),
+            d => bail!("{} isn't a colour", d),
         }
     }
 
-    Ok(best_count)
+    fn digit(self) -> u8 {
+        self as u8
+    }
 }
 
-fn part1(input: &str) -> Result<i32> {
-    checksum(input, 25 * 6)
+/// An image in the Space Image Format: layers of `width` by `height` digits,
+/// the top one first.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct SpaceImage {
+    width: usize,
+    height: usize,
+    layers: Vec<Vec<u8>>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Pixel {
-    Transparent,
-    White,
-    Black,
-}
+impl SpaceImage {
+    fn decode(digits: &str, width: usize, height: usize) -> Result<Self> {
+        let size = width * height;
+        if size == 0 {
+            bail!("empty image size {}x{}", width, height);
+        }
+        let digits = digits
+            .trim_end()
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => Ok(d as u8),
+                None => bail!("unexpected character {:?}", c),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if digits.is_empty() || digits.len() % size != 0 {
+            bail!(
+                "{} digits don't make layers of {}x{}",
+                digits.len(),
+                width,
+                height
+            );
+        }
+        Ok(SpaceImage {
+            width,
+            height,
+            layers: digits.chunks(size).map(|l| l.to_vec()).collect(),
+        })
+    }
 
-fn write_image(input: &str, width: usize, height: usize) -> Result<String> {
-    let mut buf = Vec::new();
-    use Pixel::*;
-    let size = width * height;
-    let mut output = vec![Transparent; size];
-    for layer in input.chars().collect::<Vec<_>>().chunks(size) {
-        for (digit, pixel) in layer.iter().zip(output.iter_mut()) {
-            if *pixel != Transparent {
-                continue;
-            }
-            match digit {
-                '0' => *pixel = Black,
-                '1' => *pixel = White,
-                '2' => {}
-                c => unreachable!("unexpected digit: {:?}", c),
+    /// A single layer image.
+    fn from_pixels(width: usize, height: usize, pixels: &[Pixel]) -> Result<Self> {
+        if pixels.len() != width * height {
+            bail!("{} pixels don't make {}x{}", pixels.len(), width, height);
+        }
+        Ok(SpaceImage {
+            width,
+            height,
+            layers: vec![pixels.iter().map(|p| p.digit()).collect()],
+        })
+    }
+
+    fn encode(&self) -> String {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|&d| char::from(b'0' + d))
+            .collect()
+    }
+
+    fn layers(&self) -> &[Vec<u8>] {
+        &self.layers
+    }
+
+    /// The number of 1s times the number of 2s in the layer with the fewest
+    /// 0s.
+    fn checksum(&self) -> usize {
+        let count = |layer: &[u8], digit| layer.iter().filter(|&&d| d == digit).count();
+        self.layers()
+            .iter()
+            .min_by_key(|layer| count(layer, 0))
+            .map_or(0, |layer| count(layer, 1) * count(layer, 2))
+    }
+
+    /// Each pixel's topmost colour, transparent where every layer is.
+    fn composite(&self) -> Result<Vec<Pixel>> {
+        let mut output = vec![Pixel::Transparent; self.width * self.height];
+        for layer in self.layers() {
+            for (&digit, pixel) in layer.iter().zip(output.iter_mut()) {
+                let colour = Pixel::from_digit(digit)?;
+                if *pixel == Pixel::Transparent {
+                    *pixel = colour;
+                }
             }
         }
+        Ok(output)
+    }
+
+    /// The composited image as a single layer.
+    fn flatten(&self) -> Result<Self> {
+        SpaceImage::from_pixels(self.width, self.height, &self.composite()?)
+    }
+
+    /// The composited image with white pixels as `#`, black ones as spaces
+    /// and transparent ones as `.`.
+    fn render(&self) -> Result<String> {
+        Ok(self
+            .scaled(1)?
+            .into_iter()
+            .map(|row| {
+                let mut line: String = row
+                    .into_iter()
+                    .map(|p| match p {
+                        Pixel::White => '#',
+                        Pixel::Black => ' ',
+                        Pixel::Transparent => '.',
+                    })
+                    .collect();
+                line.push('\n');
+                line
+            })
+            .collect())
     }
-    for row in output.chunks(width) {
-        for c in row.iter().map(|p| match p {
-            Transparent => unreachable!("transparent pixel"),
-            White => '#',
-            Black => ' ',
-        }) {
-            write!(&mut buf, "{}", c)?;
+
+    /// The composited rows, each pixel blown up to `scale` by `scale`.
+    fn scaled(&self, scale: usize) -> Result<Vec<Vec<Pixel>>> {
+        if scale == 0 {
+            bail!("can't scale by 0");
         }
-        write!(&mut buf, "\n")?;
+        let mut rows = vec![];
+        for row in self.composite()?.chunks(self.width) {
+            let row: Vec<_> = row.iter().flat_map(|&p| iter::repeat_n(p, scale)).collect();
+            rows.extend(iter::repeat_n(row, scale));
+        }
+        Ok(rows)
+    }
+
+    /// A plain PBM bitmap; transparent pixels come out white.
+    fn to_pbm(&self, scale: usize) -> Result<String> {
+        let rows = self.scaled(scale)?;
+        Ok(netpbm("P1", None, rows, |p| match p {
+            Pixel::Black => 1,
+            _ => 0,
+        }))
+    }
+
+    /// A plain PGM greymap, with transparent pixels in grey.
+    fn to_pgm(&self, scale: usize) -> Result<String> {
+        let rows = self.scaled(scale)?;
+        Ok(netpbm("P2", Some(2), rows, |p| match p {
+            Pixel::Black => 0,
+            Pixel::Transparent => 1,
+            Pixel::White => 2,
+        }))
+    }
+}
+
+/// A plain (ASCII) netpbm file, `value` giving each pixel's number.
+fn netpbm<F>(magic: &str, max: Option<u8>, rows: Vec<Vec<Pixel>>, value: F) -> String
+where
+    F: Fn(Pixel) -> u8,
+{
+    let width = rows.first().map_or(0, Vec::len);
+    let mut output = format!("{}\n{} {}\n", magic, width, rows.len());
+    if let Some(max) = max {
+        output += &format!("{}\n", max);
+    }
+    for row in rows {
+        let values: Vec<_> = row.into_iter().map(|p| value(p).to_string()).collect();
+        output += &values.join(" ");
+        output.push('\n');
     }
-    str::from_utf8(&buf)
-        .map_err(|_| err_msg("Failed to convert to string"))
-        .map(|s| s.to_string())
+    output
+}
+
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+/// The image at `-o width=N` by `-o height=N`, the puzzle's by default.
+fn image(input: &str, options: &Options) -> Result<SpaceImage> {
+    SpaceImage::decode(
+        input,
+        options.get("width", WIDTH)?,
+        options.get("height", HEIGHT)?,
+    )
+}
+
+/// The composited image, encoded again.
+fn flatten(input: &str, options: &Options) -> Result<String> {
+    Ok(image(input, options)?.flatten()?.encode())
+}
+
+/// The composited image as a PBM, each pixel `-o scale=N` pixels wide.
+fn pbm(input: &str, options: &Options) -> Result<String> {
+    image(input, options)?.to_pbm(options.get("scale", 1)?)
+}
+
+/// The composited image as a PGM, each pixel `-o scale=N` pixels wide.
+fn pgm(input: &str, options: &Options) -> Result<String> {
+    image(input, options)?.to_pgm(options.get("scale", 1)?)
+}
+
+fn part1(input: &str) -> Result<usize> {
+    Ok(SpaceImage::decode(input, WIDTH, HEIGHT)?.checksum())
+}
+
+fn write_image(input: &str, width: usize, height: usize) -> Result<String> {
+    SpaceImage::decode(input, width, height)?.render()
 }
 
 fn part2(input: &str) -> Result<String> {
-    write_image(input, 25, 6)
+    write_image(input, WIDTH, HEIGHT)
 }
 
 #[cfg(test)]
@@ -81,7 +241,7 @@ mod tests {
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(checksum("123456789012", 2 * 3)?, 1);
+        assert_eq!(SpaceImage::decode("123456789012", 3, 2)?.checksum(), 1);
         Ok(())
     }
 
@@ -90,4 +250,35 @@ mod tests {
         assert_eq!(write_image("0222112222120000", 2, 2)?, " #\n# \n");
         Ok(())
     }
+
+    #[test]
+    fn test_codec() -> Result<()> {
+        let image = SpaceImage::decode("0222112222120000\n", 2, 2)?;
+        assert_eq!(image.layers().len(), 4);
+        assert_eq!(image.layers()[1], vec![1, 1, 2, 2]);
+        assert_eq!(image.encode(), "0222112222120000");
+        let flat = image.flatten()?;
+        assert_eq!(flat.encode(), "0110");
+        assert_eq!(flat.render()?, image.render()?);
+
+        let partly = SpaceImage::decode("2212", 2, 1)?;
+        assert_eq!(partly.composite()?, vec![Pixel::White, Pixel::Transparent]);
+        assert_eq!(partly.render()?, "#.\n");
+
+        assert!(SpaceImage::decode("02221", 2, 2).is_err());
+        assert!(SpaceImage::decode("02x2", 2, 2).is_err());
+        assert!(SpaceImage::decode("", 2, 2).is_err());
+        assert!(SpaceImage::decode("0123", 2, 2)?.composite().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> Result<()> {
+        let image = SpaceImage::decode("21", 2, 1)?;
+        assert_eq!(image.to_pbm(1)?, "P1\n2 1\n0 0\n");
+        assert_eq!(image.to_pgm(2)?, "P2\n4 2\n2\n1 1 2 2\n1 1 2 2\n");
+        assert!(image.to_pbm(0).is_err());
+        assert!(SpaceImage::from_pixels(2, 2, &[Pixel::White]).is_err());
+        Ok(())
+    }
 }
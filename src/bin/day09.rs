@@ -7,7 +7,7 @@ fn main() -> Result<()> {
 }
 
 fn calculate(program_str: &str, input_values: &[i64]) -> Result<Vec<i64>> {
-    let mut cpu = Cpu::from_str(program_str);
+    let mut cpu = Cpu::from_str(program_str)?;
     for input_value in input_values.iter() {
         cpu.enqueue_input(*input_value);
     }
@@ -1,37 +1,37 @@
-use aoc2019::{dispatch, Result};
-use failure::bail;
+use aoc2019::parse::grid;
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::format_err;
 use num::integer::gcd;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(
+        &part1,
+        &part2,
+        &[("counts", &counts), ("vaporize", &vaporize)],
+    )
 }
 
-fn coordinates(input: &str) -> Vec<(i32, i32)> {
-    let mut row = 0;
-    let mut col = 0;
-    let mut result = vec![];
-    for c in input.chars() {
-        match c {
-            '#' => result.push((col, row)),
-            '.' => {}
-            '\n' => {
-                row += 1;
-                col = -1;
-            }
-            c => unreachable!("unexpected char {}", c),
-        }
-        col += 1;
-    }
-    result
+fn coordinates(input: &str) -> Result<Vec<(i32, i32)>> {
+    let cells = grid(input, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    Ok(cells
+        .into_iter()
+        .filter(|&(_, asteroid)| asteroid)
+        .map(|(pos, _)| (pos.x as i32, pos.y as i32))
+        .collect())
 }
 
 type Coor = (i32, i32);
 
-fn visibility(station: Coor, asteroids: &[Coor]) -> HashMap<Coor, bool> {
+/// The other asteroids, keyed by their reduced direction from `station`,
+/// each line of sight sorted from nearest to furthest.
+fn lines_of_sight(station: Coor, asteroids: &[Coor]) -> HashMap<Coor, Vec<Coor>> {
     let mut grouped = HashMap::new();
-
     for &(x, y) in asteroids.iter() {
         let dx = x - station.0;
         let dy = y - station.1;
@@ -39,112 +39,196 @@ fn visibility(station: Coor, asteroids: &[Coor]) -> HashMap<Coor, bool> {
             continue;
         }
         let d = gcd(dx, dy);
-        let key = (dx / d, dy / d);
-        let val: Coor = (x, y);
-        grouped.entry(key).or_insert(vec![]).push((d, val));
+        grouped
+            .entry((dx / d, dy / d))
+            .or_insert_with(Vec::new)
+            .push((d, (x, y)));
     }
+    grouped
+        .into_iter()
+        .map(|(direction, mut entries)| {
+            entries.sort();
+            (direction, entries.into_iter().map(|(_, pos)| pos).collect())
+        })
+        .collect()
+}
 
+fn visibility(station: Coor, asteroids: &[Coor]) -> HashMap<Coor, bool> {
     let mut result = HashMap::new();
-    for (_key, entries) in grouped {
-        let mut distances: Vec<_> = entries.iter().map(|(d, _)| d).collect();
-        distances.sort();
-        let &smallest = distances[0];
-
-        for (d, val) in entries {
-            result.insert(val, d == smallest);
+    for (_direction, line) in lines_of_sight(station, asteroids) {
+        for (i, pos) in line.into_iter().enumerate() {
+            result.insert(pos, i == 0);
         }
     }
-
     result
 }
 
-fn find_max(input: &str) -> (usize, i32, i32) {
-    let mut counts = vec![];
+/// Orders directions clockwise starting from straight up, `y` growing
+/// downwards. Exact, as long as no two directions are multiples of each other.
+fn clockwise(a: Coor, b: Coor) -> Ordering {
+    // up and everything right of it come first, then down and everything left
+    let half = |(dx, dy): Coor| dx < 0 || (dx == 0 && dy > 0);
+    half(a)
+        .cmp(&half(b))
+        .then_with(|| (a.1 * b.0).cmp(&(a.0 * b.1)))
+}
 
-    let asteroids = coordinates(input);
-    for (x, y) in asteroids.iter() {
-        let mut visible = HashSet::new();
-        for (nx, ny) in asteroids.iter() {
-            let dx = nx - x;
-            let dy = ny - y;
-            if (dx, dy) == (0, 0) {
-                continue;
-            }
-            let d = gcd(dx, dy);
-            visible.insert((dx / d, dy / d));
+/// Every other asteroid, in the order a laser at `station` turning
+/// clockwise from straight up vaporizes them.
+fn vaporization_order(station: Coor, asteroids: &[Coor]) -> Vec<Coor> {
+    let mut lines: Vec<_> = lines_of_sight(station, asteroids).into_iter().collect();
+    lines.sort_by(|a, b| clockwise(a.0, b.0));
+    let rotations = lines.iter().map(|(_, line)| line.len()).max().unwrap_or(0);
+    (0..rotations)
+        .flat_map(|rotation| lines.iter().filter_map(move |(_, line)| line.get(rotation)))
+        .cloned()
+        .collect()
+}
+
+/// The most asteroids visible from any asteroid, and every asteroid that
+/// many are visible from, in reading order.
+fn find_max(input: &str) -> Result<(usize, Vec<Coor>)> {
+    let asteroids = coordinates(input)?;
+    let mut best = (0, vec![]);
+    for &station in asteroids.iter() {
+        let count = lines_of_sight(station, &asteroids).len();
+        if count > best.0 {
+            best = (count, vec![]);
+        }
+        if count == best.0 {
+            best.1.push(station);
         }
-        counts.push((visible.len(), *x, *y));
     }
-    counts.sort_by_key(|&t| t.0);
-
-    counts[counts.len() - 1]
+    Ok(best)
 }
 
 fn part1(input: &str) -> Result<usize> {
-    Ok(find_max(input).0)
+    Ok(find_max(input)?.0)
 }
 
 fn part2(input: &str) -> Result<i32> {
-    let (_, station_x, station_y) = find_max(input);
-    let asteroids = coordinates(input);
-    let mut aset: HashSet<_> = asteroids.iter().clone().collect();
-    aset.remove(&(station_x, station_y));
-    let mut n = 1;
+    let (_, stations) = find_max(input)?;
+    let station = *stations
+        .first()
+        .ok_or_else(|| format_err!("no asteroids"))?;
+    let order = vaporization_order(station, &coordinates(input)?);
+    let (x, y) = order
+        .get(199)
+        .ok_or_else(|| format_err!("only {} asteroids to vaporize", order.len()))?;
+    Ok(x * 100 + y)
+}
 
-    while aset.len() > 0 {
-        let remaining: Vec<Coor> = aset.iter().map(|&(a, b)| (*a, *b)).collect();
-        let visibility = visibility((station_x, station_y), &remaining[..]);
-        let mut visible = vec![];
-        for &(x, y) in &remaining {
-            let dx = x - station_x;
-            let dy = y - station_y;
-            if (dx, dy) == (0, 0) {
-                continue;
-            }
-            if !visibility.get(&(x, y)).expect("visibility unknown") {
-                continue;
-            }
-            let quadrant = match (dx.signum(), dy.signum()) {
-                (0, -1) => 0,
-                (1, -1) => 1,
-                (1, 0) => 2,
-                (1, 1) => 3,
-                (0, 1) => 4,
-                (-1, 1) => 5,
-                (-1, 0) => 6,
-                (-1, -1) => 7,
-                _ => unreachable!("bad signum?"),
-            };
-            visible.push((quadrant, dx, dy, x, y));
-        }
-        visible.sort_by(|a, b| match a.0.cmp(&b.0) {
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => (a.2 * b.1).cmp(&(a.1 * b.2)),
-            Ordering::Greater => Ordering::Greater,
-        });
-        let visible: Vec<_> = visible.iter().map(|&(_q, _dx, _dy, x, y)| (x, y)).collect();
-        for entry in visible.into_iter() {
-            if n == 200 {
-                return Ok(entry.0 * 100 + entry.1);
-            }
-            aset.remove(&entry);
-            n += 1;
-        }
-    }
-    bail!("Didn't get to 200");
+/// Redraws the field with each asteroid in `labels` replaced by its label,
+/// padding every cell to the widest one.
+fn overlay(input: &str, labels: &HashMap<Coor, String>) -> String {
+    let width = labels.values().map(String::len).max().unwrap_or(1);
+    let separator = if width > 1 { " " } else { "" };
+    input
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(col, c)| match labels.get(&(col as i32, row as i32)) {
+                    Some(label) => format!("{:>w$}", label, w = width),
+                    None => format!("{:>w$}", c, w = width),
+                })
+                .collect::<Vec<_>>()
+                .join(separator)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-// y1/x1 > y2/x2 -> y1x2 > y2x1
-//  -1,-1 | 1,-1
-//  --------------
-//  -1,1  | 1,1
+fn parse_station(value: &str) -> Result<Coor> {
+    let split = value
+        .find(',')
+        .ok_or_else(|| format_err!("expected X,Y, got {:?}", value))?;
+    let coordinate = |s: &str| {
+        s.trim()
+            .parse::<i32>()
+            .map_err(|e| format_err!("bad station {:?}: {}", value, e))
+    };
+    Ok((
+        coordinate(&value[..split])?,
+        coordinate(&value[split + 1..])?,
+    ))
+}
+
+/// How many other asteroids each asteroid sees, and the best stations.
+fn counts(input: &str, _options: &Options) -> Result<String> {
+    let asteroids = coordinates(input)?;
+    let labels = asteroids
+        .iter()
+        .map(|&pos| {
+            let seen = visibility(pos, &asteroids).values().filter(|&&v| v).count();
+            (pos, seen.to_string())
+        })
+        .collect();
+    let (best, stations) = find_max(input)?;
+    let stations: Vec<_> = stations
+        .iter()
+        .map(|(x, y)| format!("{},{}", x, y))
+        .collect();
+    Ok(format!(
+        "{}\nbest: {} from {}",
+        overlay(input, &labels),
+        best,
+        stations.join(" ")
+    ))
+}
+
+/// The order the first `-o count=N` asteroids get vaporized in, all of them
+/// by default, with the station at `-o station=X,Y` or the best one.
+fn vaporize(input: &str, options: &Options) -> Result<String> {
+    let asteroids = coordinates(input)?;
+    let station = match options.get_str("station") {
+        Some(value) => parse_station(value)?,
+        None => *find_max(input)?
+            .1
+            .first()
+            .ok_or_else(|| format_err!("no asteroids"))?,
+    };
+    let order = vaporization_order(station, &asteroids);
+    let mut labels: HashMap<_, _> = order
+        .iter()
+        .take(options.get("count", order.len())?)
+        .enumerate()
+        .map(|(i, &pos)| (pos, (i + 1).to_string()))
+        .collect();
+    labels.insert(station, "X".into());
+    Ok(overlay(input, &labels))
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2019::parse::ParseError;
+
+    const LARGE: &str = "\
+.#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##";
 
     #[test]
-    fn test_coordinates() {
+    fn test_coordinates() -> Result<()> {
         assert_eq!(
             coordinates(
                 ".#..#
@@ -152,7 +236,7 @@ mod tests {
 #####
 ....#
 ...##"
-            ),
+            )?,
             vec![
                 (1, 0),
                 (4, 0),
@@ -166,6 +250,15 @@ mod tests {
                 (4, 4)
             ]
         );
+        let e = coordinates(".#\r\n#x.\n")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!((e.line, e.column), (2, 2));
+        let options = Options::parse(vec![])?;
+        assert!(counts("#?", &options).is_err());
+        assert!(vaporize("#?", &options).is_err());
+        Ok(())
     }
 
     #[test]
@@ -184,7 +277,7 @@ mod tests {
     }
 
     #[test]
-    fn test_find_max1() {
+    fn test_find_max1() -> Result<()> {
         assert_eq!(
             find_max(
                 "......#.#.
@@ -197,13 +290,14 @@ mod tests {
 .##.#..###
 ##...#..#.
 .#....####"
-            ),
-            (33, 5, 8)
+            )?,
+            (33, vec![(5, 8)])
         );
+        Ok(())
     }
 
     #[test]
-    fn test_find_max2() {
+    fn test_find_max2() -> Result<()> {
         assert_eq!(
             find_max(
                 "#.#...#.#.
@@ -216,42 +310,20 @@ mod tests {
 ..##....##
 ......#...
 .####.###."
-            ),
-            (35, 1, 2)
+            )?,
+            (35, vec![(1, 2)])
         );
+        Ok(())
     }
 
     #[test]
-    fn test_find_max3() {
-        assert_eq!(
-            find_max(
-                ".#..##.###...#######
-##.############..##.
-.#.######.########.#
-.###.#######.####.#.
-#####.##.#.##.###.##
-..#####..#.#########
-####################
-#.####....###.#.#.##
-##.#################
-#####.##.###..####..
-..######..##.#######
-####.##.####...##..#
-.#####..#.######.###
-##...#.##########...
-#.##########.#######
-.####.#.###.###.#.##
-....##.##.###..#####
-.#.#.###########.###
-#.#.#.#####.####.###
-###.##.####.##.#..##"
-            ),
-            (210, 11, 13)
-        );
+    fn test_find_max3() -> Result<()> {
+        assert_eq!(find_max(LARGE)?, (210, vec![(11, 13)]));
+        Ok(())
     }
 
     #[test]
-    fn test_part2_init() {
+    fn test_part2_init() -> Result<()> {
         assert_eq!(
             find_max(
                 ".#....#####...#..
@@ -259,9 +331,10 @@ mod tests {
 ##...#...#.#####.
 ..#.....#...###..
 ..#.#.....#....##"
-            ),
-            (30, 8, 3)
+            )?,
+            (30, vec![(8, 3)])
         );
+        Ok(())
     }
 
     //     #[test]
@@ -280,37 +353,7 @@ mod tests {
     //     }
 
     #[test]
-    fn test_part2() -> Result<()> {
-        assert_eq!(
-            part2(
-                ".#..##.###...#######
-##.############..##.
-.#.######.########.#
-.###.#######.####.#.
-#####.##.#.##.###.##
-..#####..#.#########
-####################
-#.####....###.#.#.##
-##.#################
-#####.##.###..####..
-..######..##.#######
-####.##.####...##..#
-.#####..#.######.###
-##...#.##########...
-#.##########.#######
-.####.#.###.###.#.##
-....##.##.###..#####
-.#.#.###########.###
-#.#.#.#####.####.###
-###.##.####.##.#..##"
-            )?,
-            802
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_visibility() {
+    fn test_visibility() -> Result<()> {
         assert_eq!(
             visibility(
                 (0, 2),
@@ -320,7 +363,7 @@ mod tests {
 #####
 ....#
 ...##"
-                )
+                )?
             ),
             [
                 ((1, 0), true),
@@ -337,6 +380,107 @@ mod tests {
             .iter()
             .cloned()
             .collect::<HashMap<Coor, bool>>()
-        )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaporization_order() -> Result<()> {
+        let order = vaporization_order((11, 13), &coordinates(LARGE)?);
+        assert_eq!(order.len(), 299);
+        for &(n, pos) in [
+            (1, (11, 12)),
+            (2, (12, 1)),
+            (3, (12, 2)),
+            (10, (12, 8)),
+            (20, (16, 0)),
+            (50, (16, 9)),
+            (100, (10, 16)),
+            (199, (9, 6)),
+            (200, (8, 2)),
+            (201, (10, 9)),
+            (299, (11, 1)),
+        ]
+        .iter()
+        {
+            assert_eq!(order[n - 1], pos, "asteroid {}", n);
+        }
+
+        // from a station that isn't the best one
+        let asteroids = coordinates(
+            ".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....#...###..
+..#.#.....#....##",
+        )?;
+        assert_eq!(
+            vaporization_order((8, 3), &asteroids)[..9],
+            [
+                (8, 1),
+                (9, 0),
+                (9, 1),
+                (10, 0),
+                (9, 2),
+                (11, 1),
+                (12, 1),
+                (11, 2),
+                (15, 1)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_max_ties() -> Result<()> {
+        assert_eq!(
+            find_max("#.#\n...\n#.#")?,
+            (3, vec![(0, 0), (2, 0), (0, 2), (2, 2)])
+        );
+        assert_eq!(find_max("###")?, (2, vec![(1, 0)]));
+        assert_eq!(find_max("...")?, (0, vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_counts() -> Result<()> {
+        let options = Options::parse(vec![])?;
+        assert_eq!(
+            counts(".#..#\n.....\n#####\n....#\n...##", &options)?,
+            ".7..7\n.....\n67775\n....7\n...87\nbest: 8 from 3,4"
+        );
+        assert_eq!(
+            counts("#.#\n#..", &options)?,
+            "2.2\n2..\nbest: 2 from 0,0 2,0 0,1"
+        );
+        assert_eq!(
+            counts("#.#\r\n#..\r\n", &options)?,
+            "2.2\n2..\nbest: 2 from 0,0 2,0 0,1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaporize() -> Result<()> {
+        let input = ".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....#...###..
+..#.#.....#....##";
+        let options = Options::parse(vec!["station=8,3", "count=9"])?;
+        assert_eq!(
+            vaporize(input, &options)?,
+            ".#....###24...#..
+##...##.13#67..9#
+##...#...5.8####.
+..#.....X...###..
+..#.#.....#....##"
+        );
+        assert_eq!(
+            vaporize(".#.\n#.#\n.#.", &Options::parse(vec![])?)?,
+            ".X.\n3.1\n.2."
+        );
+        assert!(vaporize(input, &Options::parse(vec!["station=8"])?).is_err());
+        Ok(())
     }
 }
@@ -1,3 +1,4 @@
+use aoc2019::coor::{Bounds, Coor, Direction};
 use aoc2019::cpu::{Cpu, CpuState};
 use aoc2019::{dispatch, Result};
 use failure::err_msg;
@@ -9,26 +10,6 @@ fn main() -> Result<()> {
     dispatch(&part1, &part2)
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
-struct Coor {
-    x: i32,
-    y: i32,
-}
-
-impl Coor {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-}
-
 struct Robot {
     direction: Direction,
     position: Coor,
@@ -43,44 +24,13 @@ impl Robot {
     }
 }
 
-fn turn_left(direction: &Direction) -> Direction {
-    use Direction::*;
-    match direction {
-        Up => Left,
-        Left => Down,
-        Down => Right,
-        Right => Up,
-    }
-}
-
-fn turn_right(direction: &Direction) -> Direction {
-    use Direction::*;
-    match direction {
-        Up => Right,
-        Right => Down,
-        Down => Left,
-        Left => Up,
-    }
-}
-
-fn mv(position: Coor, direction: Direction) -> Coor {
-    use Direction::*;
-
-    match direction {
-        Up => Coor::new(position.x, position.y - 1),
-        Right => Coor::new(position.x + 1, position.y),
-        Down => Coor::new(position.x, position.y + 1),
-        Left => Coor::new(position.x - 1, position.y),
-    }
-}
-
 fn part1(input: &str) -> Result<usize> {
     let mut whites = HashSet::new();
     let mut paints = HashSet::new();
 
     let mut robot = Robot::new();
 
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
 
     loop {
         match cpu.run()? {
@@ -97,11 +47,11 @@ fn part1(input: &str) -> Result<usize> {
                 paints.insert(robot.position);
                 if let CpuState::Output(turn) = cpu.run()? {
                     robot.direction = match turn {
-                        0 => turn_left(&robot.direction),
-                        1 => turn_right(&robot.direction),
+                        0 => robot.direction.turn_left(),
+                        1 => robot.direction.turn_right(),
                         _ => unreachable!("Invalid turn direction"),
                     };
-                    robot.position = mv(robot.position, robot.direction);
+                    robot.position += robot.direction.offset();
                 } else {
                     unreachable!("second output missing");
                 }
@@ -123,7 +73,7 @@ fn part2(input: &str) -> Result<String> {
 
     let mut robot = Robot::new();
 
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
 
     whites.insert(robot.position);
 
@@ -142,11 +92,11 @@ fn part2(input: &str) -> Result<String> {
                 paints.insert(robot.position);
                 if let CpuState::Output(turn) = cpu.run()? {
                     robot.direction = match turn {
-                        0 => turn_left(&robot.direction),
-                        1 => turn_right(&robot.direction),
+                        0 => robot.direction.turn_left(),
+                        1 => robot.direction.turn_right(),
                         _ => unreachable!("Invalid turn direction"),
                     };
-                    robot.position = mv(robot.position, robot.direction);
+                    robot.position += robot.direction.offset();
                 } else {
                     unreachable!("second output missing");
                 }
@@ -159,21 +109,12 @@ fn part2(input: &str) -> Result<String> {
             CpuState::Halted => break,
         }
     }
-    // dbg!(whites);
-    let mut v: Vec<_> = whites.iter().collect();
-    v.sort_by_key(|t| -t.x);
-    let max_x = v[0].x;
-
-    v.sort_by_key(|t| -t.y);
-    let max_y = v[0].y;
-
-    // dbg!(&whites.contains(&Coor::new(26, 5)));
+    let bounds = Bounds::of(&whites).ok_or_else(|| err_msg("Nothing painted"))?;
 
     let mut buf = Vec::new();
 
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            // dbg!(x, y);
+    for y in 0..=bounds.max.y {
+        for x in 0..=bounds.max.x {
             let output = if whites.contains(&Coor::new(x, y)) {
                 "#"
             } else {
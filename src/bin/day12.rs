@@ -1,286 +1,405 @@
-use aoc2019::{dispatch, Result};
-use failure::Error;
+use aoc2019::cycle::{combined_period, period};
+use aoc2019::parse::{captures, field, parse_lines, shift, split_with, ParseError};
+use aoc2019::vector::{DynVector, Vector};
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::bail;
 use lazy_static::lazy_static;
-use num::integer::lcm;
 use regex::Regex;
-use std::ops::AddAssign;
-use std::str::FromStr;
+use std::time::Instant;
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(
+        &part1,
+        &part2,
+        &[
+            ("trajectory", &trajectory),
+            ("energy", &energy),
+            ("periods", &periods),
+            ("bench", &bench),
+        ],
+    )
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Coor {
-    x: i32,
-    y: i32,
-    z: i32,
-}
+/// Values by axis name.
+type Named = Vec<(String, i64)>;
 
-impl Coor {
-    fn new(x: i32, y: i32, z: i32) -> Self {
-        Coor { x, y, z }
+/// The named axes of a vector like `<x=-1, y=7, z=3>`, any number of them.
+fn parse_vector(s: &str) -> Result<Named> {
+    lazy_static! {
+        static ref AXIS: Regex = Regex::new(r"^ *(\w+)= *(-?\d+) *$").expect("regex create");
     }
 
-    fn energy(&self) -> i32 {
-        self.x.abs() + self.y.abs() + self.z.abs()
+    if s.len() < 2 || !s.starts_with('<') || !s.ends_with('>') {
+        return Err(ParseError::new(1, 1, &format!("expected <...>, got {:?}", s)).into());
     }
+    shift(
+        split_with(&s[1..s.len() - 1], ",", |token| {
+            let caps = captures(&AXIS, token)?;
+            Ok((caps[1].to_string(), field(&caps, 2)?))
+        }),
+        1,
+    )
 }
 
-impl FromStr for Coor {
-    type Err = Error;
+/// A body's position, or its position and velocity as in
+/// `pos=<x= 2, y=-1>, vel=<x= 3, y=-1>`.
+fn parse_body(line: &str) -> Result<(Named, Named)> {
+    const POS: &str = "pos=";
+    const VEL: &str = ", vel=";
+
+    if !line.starts_with(POS) {
+        let position = parse_vector(line)?;
+        let velocity = position.iter().map(|(name, _)| (name.clone(), 0)).collect();
+        return Ok((position, velocity));
+    }
+    let split = line
+        .find(VEL)
+        .ok_or_else(|| ParseError::new(1, line.len() + 1, "expected a velocity"))?;
+    let position = shift(parse_vector(&line[POS.len()..split]), POS.len())?;
+    let start = split + VEL.len();
+    let velocity = shift(parse_vector(&line[start..]), start)?;
+    Ok((position, velocity))
+}
 
-    fn from_str(s: &str) -> Result<Self> {
-        lazy_static! {
-            static ref RE: Regex =
-            // <x=12, y=2, z=-13>
-                Regex::new(r"<x= *(-?\d+), y= *(-?\d+), z= *(-?\d+)>")
-                    .expect("regex create");
+/// Every body's position and velocity along one axis. Axes don't affect
+/// each other, so each can be simulated on its own.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+struct Axis {
+    positions: Vec<Vector<1>>,
+    velocities: Vec<Vector<1>>,
+}
+
+impl Axis {
+    fn step(&mut self) {
+        let positions = &self.positions;
+        for (&position, velocity) in positions.iter().zip(self.velocities.iter_mut()) {
+            for &other in positions {
+                *velocity += (other - position).signum();
+            }
         }
+        for (position, &velocity) in self.positions.iter_mut().zip(&self.velocities) {
+            *position += velocity;
+        }
+    }
 
-        let caps = RE.captures(s).expect("regex match");
-        Ok(Self::new(
-            caps[1].parse().expect("regex match 1"),
-            caps[2].parse().expect("regex match 2"),
-            caps[3].parse().expect("regex match 3"),
-        ))
+    fn still(&self) -> bool {
+        self.velocities.iter().all(|&v| v == Vector::zero())
+    }
+
+    /// Steps until the axis is back where it started. A step can be undone,
+    /// so the first state to come back is always the start.
+    fn period(&self) -> usize {
+        if self.still() {
+            self.half_period()
+        } else {
+            self.full_period()
+        }
     }
-}
 
-impl AddAssign for Coor {
-    fn add_assign(&mut self, other: Coor) {
-        self.x += other.x;
-        self.y += other.y;
-        self.z += other.z;
+    /// Compares every state against the start, stepping a single copy.
+    fn full_period(&self) -> usize {
+        let mut axis = self.clone();
+        let mut steps = 0;
+        loop {
+            axis.step();
+            steps += 1;
+            if axis == *self {
+                return steps;
+            }
+        }
+    }
+
+    /// For an axis that starts still, whose motion is then symmetric in time:
+    /// once it's still again after `t` steps, it retraces its path back to
+    /// the start, so that comes back after `t` steps or `2t`.
+    fn half_period(&self) -> usize {
+        debug_assert!(self.still());
+        let mut axis = self.clone();
+        let mut steps = 0;
+        loop {
+            axis.step();
+            steps += 1;
+            if axis.still() {
+                return if axis.positions == self.positions {
+                    steps
+                } else {
+                    2 * steps
+                };
+            }
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Moon {
-    position: Coor,
-    velocity: Coor,
+/// Any number of bodies, in any number of dimensions.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct System {
+    names: Vec<String>,
+    positions: Vec<DynVector>,
+    velocities: Vec<DynVector>,
 }
 
-impl Moon {
-    fn new(position: Coor, velocity: Coor) -> Self {
-        Self { position, velocity }
+impl System {
+    fn bodies(&self) -> usize {
+        self.positions.len()
     }
 
-    fn init(position: Coor) -> Self {
-        Self::new(position, Coor::new(0, 0, 0))
+    fn step(&mut self) {
+        let positions = &self.positions;
+        for (position, velocity) in positions.iter().zip(self.velocities.iter_mut()) {
+            for other in positions {
+                *velocity += &(other - position).signum();
+            }
+        }
+        for (position, velocity) in self.positions.iter_mut().zip(&self.velocities) {
+            *position += velocity;
+        }
     }
 
-    fn energy(&self) -> i32 {
-        self.position.energy() * self.velocity.energy()
+    fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Potential and kinetic energy of each body.
+    fn energies(&self) -> Vec<(i64, i64)> {
+        self.positions
+            .iter()
+            .zip(&self.velocities)
+            .map(|(position, velocity)| (position.norm(), velocity.norm()))
+            .collect()
     }
-}
 
-impl FromStr for Moon {
-    type Err = Error;
+    fn energy(&self) -> i64 {
+        self.energies().iter().map(|(pot, kin)| pot * kin).sum()
+    }
 
-    fn from_str(s: &str) -> Result<Self> {
-        lazy_static! {
-            static ref RE: Regex =
-            // pos=<x= 2, y=-1, z= 1>, vel=<x= 3, y=-1, z=-1>
-                Regex::new(r"pos=<x= *(-?\d+), y= *(-?\d+), z= *(-?\d+)>, vel=<x= *(-?\d+), y= *(-?\d+), z= *(-?\d+)>")
-                    .expect("regex create");
+    /// Every body along axis `axis` alone.
+    fn axis(&self, axis: usize) -> Axis {
+        let project = |vectors: &[DynVector]| vectors.iter().map(|v| v.project(axis)).collect();
+        Axis {
+            positions: project(&self.positions),
+            velocities: project(&self.velocities),
         }
+    }
 
-        let caps = RE.captures(s).expect("regex match");
-        Ok(Self::new(
-            Coor::new(
-                caps[1].parse().expect("regex match 1"),
-                caps[2].parse().expect("regex match 2"),
-                caps[3].parse().expect("regex match 3"),
-            ),
-            Coor::new(
-                caps[4].parse().expect("regex match 4"),
-                caps[5].parse().expect("regex match 5"),
-                caps[6].parse().expect("regex match 6"),
-            ),
-        ))
+    fn axes(&self) -> Vec<Axis> {
+        (0..self.names.len()).map(|axis| self.axis(axis)).collect()
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct SingleMoon {
-    position: i32,
-    velocity: i32,
-}
+    /// Steps until each axis comes back to its current state.
+    fn periods(&self) -> Vec<usize> {
+        self.axes().iter().map(Axis::period).collect()
+    }
 
-fn parse(input: &str) -> Result<Vec<Coor>> {
-    input.split('\n').map(|row| row.parse()).collect()
+    /// Steps until the whole system comes back to its current state.
+    fn period(&self) -> usize {
+        combined_period(self.periods())
+    }
 }
 
-fn _parse_moon(input: &str) -> Result<Vec<Moon>> {
-    input.split('\n').map(|row| row.parse()).collect()
+/// One body per line; every body must have the same axes.
+fn parse(input: &str) -> Result<System> {
+    let bodies = parse_lines(input, parse_body)?;
+    let names = |v: &Named| v.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+    let expected = bodies
+        .first()
+        .map_or(vec![], |(position, _)| names(position));
+    let vector = |v: &Named| DynVector::new(v.iter().map(|&(_, a)| a).collect());
+    let mut system = System {
+        names: expected,
+        positions: vec![],
+        velocities: vec![],
+    };
+    for (idx, (position, velocity)) in bodies.iter().enumerate() {
+        if names(position) != system.names || names(velocity) != system.names {
+            let message = format!("expected axes {}", system.names.join(", "));
+            return Err(ParseError::new(idx + 1, 1, &message).into());
+        }
+        system.positions.push(vector(position));
+        system.velocities.push(vector(velocity));
+    }
+    Ok(system)
 }
 
-fn make_moons(coors: &[Coor]) -> Vec<Moon> {
-    coors.iter().map(|&pos| Moon::init(pos)).collect()
+/// Every `-o every=N`th step up to `-o steps=N` as CSV, starting with `header`.
+fn sample<F>(input: &str, options: &Options, header: &str, mut row: F) -> Result<String>
+where
+    F: FnMut(&mut String, usize, &System),
+{
+    let mut system = parse(input)?;
+    let steps = options.get("steps", 1000)?;
+    let every = options.get("every", 1)?;
+    if every == 0 {
+        bail!("every must be positive");
+    }
+    let mut csv = header.to_string();
+    for step in 0..=steps {
+        if step % every == 0 {
+            row(&mut csv, step, &system);
+        }
+        system.step();
+    }
+    Ok(csv)
 }
 
-fn split_moons(moons: &[Moon]) -> (Vec<SingleMoon>, Vec<SingleMoon>, Vec<SingleMoon>) {
-    let moons_x = moons
-        .iter()
-        .map(|m| SingleMoon {
-            position: m.position.x,
-            velocity: m.velocity.x,
-        })
-        .collect();
-    let moons_y = moons
-        .iter()
-        .map(|m| SingleMoon {
-            position: m.position.y,
-            velocity: m.velocity.y,
-        })
-        .collect();
-    let moons_z = moons
-        .iter()
-        .map(|m| SingleMoon {
-            position: m.position.z,
-            velocity: m.velocity.z,
-        })
-        .collect();
-    (moons_x, moons_y, moons_z)
+fn csv_line<T: ToString>(values: &[T]) -> String {
+    let values: Vec<_> = values.iter().map(T::to_string).collect();
+    values.join(",") + "\n"
 }
 
-fn apply_gravity(moons: &mut Vec<Moon>) {
-    for i in 0..moons.len() {
-        for j in i..moons.len() {
-            if moons[i].position.x > moons[j].position.x {
-                moons[i].velocity.x -= 1;
-                moons[j].velocity.x += 1;
-            } else if moons[i].position.x < moons[j].position.x {
-                moons[i].velocity.x += 1;
-                moons[j].velocity.x -= 1;
-            }
-
-            if moons[i].position.y > moons[j].position.y {
-                moons[i].velocity.y -= 1;
-                moons[j].velocity.y += 1;
-            } else if moons[i].position.y < moons[j].position.y {
-                moons[i].velocity.y += 1;
-                moons[j].velocity.y -= 1;
-            }
-
-            if moons[i].position.z > moons[j].position.z {
-                moons[i].velocity.z -= 1;
-                moons[j].velocity.z += 1;
-            } else if moons[i].position.z < moons[j].position.z {
-                moons[i].velocity.z += 1;
-                moons[j].velocity.z -= 1;
-            }
+/// Each body's position and velocity over time, as CSV.
+fn trajectory(input: &str, options: &Options) -> Result<String> {
+    let names = parse(input)?.names;
+    let mut header = vec!["step".to_string(), "body".to_string()];
+    header.extend(names.iter().cloned());
+    header.extend(names.iter().map(|name| format!("v{}", name)));
+    sample(input, options, &csv_line(&header), |csv, step, system| {
+        for body in 0..system.bodies() {
+            let mut values = vec![step as i64, body as i64];
+            values.extend(system.positions[body].axes());
+            values.extend(system.velocities[body].axes());
+            *csv += &csv_line(&values);
         }
-    }
+    })
 }
 
-fn apply_gravity_single(moons: &mut Vec<SingleMoon>) {
-    for i in 0..moons.len() {
-        for j in i..moons.len() {
-            if moons[i].position > moons[j].position {
-                moons[i].velocity -= 1;
-                moons[j].velocity += 1;
-            } else if moons[i].position < moons[j].position {
-                moons[i].velocity += 1;
-                moons[j].velocity -= 1;
-            }
-        }
-    }
+/// The system's potential, kinetic and total energy over time, as CSV.
+fn energy(input: &str, options: &Options) -> Result<String> {
+    let header = "step,potential,kinetic,total\n";
+    sample(input, options, header, |csv, step, system| {
+        let energies = system.energies();
+        let potential: i64 = energies.iter().map(|(pot, _)| pot).sum();
+        let kinetic: i64 = energies.iter().map(|(_, kin)| kin).sum();
+        *csv += &csv_line(&[step as i64, potential, kinetic, system.energy()]);
+    })
 }
 
-fn apply_velocity(moons: &mut Vec<Moon>) {
-    for moon in moons.iter_mut() {
-        moon.position += moon.velocity;
+/// Each axis's period, and the whole system's.
+fn periods(input: &str, _options: &Options) -> Result<String> {
+    let system = parse(input)?;
+    let mut report = String::new();
+    for (name, period) in system.names.iter().zip(system.periods()) {
+        report += &format!("{}: {}\n", name, period);
     }
+    report += &format!("all: {}", system.period());
+    Ok(report)
 }
 
-fn apply_velocity_single(moons: &mut Vec<SingleMoon>) {
-    for moon in moons.iter_mut() {
-        moon.position += moon.velocity;
-    }
-}
+type PeriodFinder = fn(&Axis) -> usize;
 
-fn simulate(moons: &mut Vec<Moon>, steps: usize) {
-    for _ in 0..steps {
-        apply_gravity(moons);
-        apply_velocity(moons);
-    }
+/// The generic way, stepping a fresh copy of each state.
+fn copying_period(start: &Axis) -> usize {
+    period(start, |axis| {
+        let mut axis = axis.clone();
+        axis.step();
+        axis
+    })
 }
 
-fn simulate_single(moons: &mut Vec<SingleMoon>) -> usize {
-    let initial = moons.clone();
+/// Times finding every axis's period by comparing a fresh copy of each
+/// state, by stepping a single copy, and from the half period when the
+/// bodies start still.
+fn bench(input: &str, _options: &Options) -> Result<String> {
+    let axes = parse(input)?.axes();
+    let mut methods: Vec<(&str, PeriodFinder)> =
+        vec![("copying", copying_period), ("in place", Axis::full_period)];
+    if axes.iter().all(Axis::still) {
+        methods.push(("half period", Axis::half_period));
+    }
 
-    let mut steps = 0;
-    loop {
-        apply_gravity_single(moons);
-        apply_velocity_single(moons);
-        steps += 1;
-        if moons
-            .iter()
-            .zip(initial.iter())
-            .all(|(a, b)| a.position == b.position && a.velocity == b.velocity)
-        {
-            break;
-        }
+    let mut report = vec![];
+    for (name, method) in methods {
+        let start = Instant::now();
+        let periods: Vec<_> = axes.iter().map(method).collect();
+        report.push(format!(
+            "{:12}  {:>10.1?}  {:?} -> {}",
+            name,
+            start.elapsed(),
+            periods,
+            combined_period(periods.iter().cloned())
+        ));
     }
-    steps
+    Ok(report.join("\n"))
 }
 
-fn part1(input: &str) -> Result<i32> {
-    let mut moons = make_moons(&parse(input)?);
-    simulate(&mut moons, 1000);
-    Ok(moons.iter().map(|m| m.energy()).sum::<i32>())
+fn part1(input: &str) -> Result<i64> {
+    let mut system = parse(input)?;
+    system.run(1000);
+    Ok(system.energy())
 }
 
 fn part2(input: &str) -> Result<usize> {
-    let moons = make_moons(&parse(input)?);
-    let (mut moons_x, mut moons_y, mut moons_z) = split_moons(&moons);
-    let steps_x = simulate_single(&mut moons_x);
-    let steps_y = simulate_single(&mut moons_y);
-    let steps_z = simulate_single(&mut moons_z);
-    Ok(lcm(lcm(steps_x, steps_y), steps_z))
+    Ok(parse(input)?.period())
 }
 
 #[cfg(test)]
 mod day_12_tests {
     use super::*;
 
+    const SAMPLE: &str = "\
+<x=-1, y=0, z=2>
+<x=2, y=-10, z=-7>
+<x=4, y=-8, z=8>
+<x=3, y=5, z=-1>";
+
+    fn positions(system: &System) -> Vec<Vec<i64>> {
+        system.positions.iter().map(|p| p.axes().to_vec()).collect()
+    }
+
     #[test]
     fn test_parse() -> Result<()> {
-        assert_eq!(
-            parse(
-                "<x=-1, y=7, z=3>
+        let system = parse(
+            "<x=-1, y=7, z=3>
 <x=12, y=2, z=-13>
 <x=14, y=18, z=-8>
-<x=17, y=4, z=-4>"
-            )?,
+<x=17, y=4, z=-4>",
+        )?;
+        assert_eq!(system.names, vec!["x", "y", "z"]);
+        assert_eq!(
+            positions(&system),
             vec![
-                Coor::new(-1, 7, 3),
-                Coor::new(12, 2, -13),
-                Coor::new(14, 18, -8),
-                Coor::new(17, 4, -4),
+                vec![-1, 7, 3],
+                vec![12, 2, -13],
+                vec![14, 18, -8],
+                vec![17, 4, -4],
             ]
         );
+        assert_eq!(system.velocities[3], DynVector::zero(3));
         Ok(())
     }
 
     #[test]
-    fn test_from_str() -> Result<()> {
-        let coor: Coor = "<x=-1, y=7, z=3>".parse()?;
-        assert_eq!(coor, Coor::new(-1, 7, 3));
+    fn test_parse_error() {
+        let e = parse("<x=-1, y=7, z=3>\n<x=12, y=2, z=->")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!(e.line, 2);
+
+        let e = parse("<x=-1, y=7>\n<x=12, y=2, z=3>")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!((e.line, e.message.as_str()), (2, "expected axes x, y"));
+    }
+
+    #[test]
+    fn test_parse_vector() -> Result<()> {
+        assert_eq!(
+            parse_vector("<x=-1, y=7, z=3>")?,
+            vec![("x".into(), -1), ("y".into(), 7), ("z".into(), 3)]
+        );
+        let e = parse_vector("<x=-1, y=?>")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!(e.column, 7);
         Ok(())
     }
 
     #[test]
     fn test_simulate() -> Result<()> {
-        let coors = parse(
-            "<x=-1, y=0, z=2>
-<x=2, y=-10, z=-7>
-<x=4, y=-8, z=8>
-<x=3, y=5, z=-1>",
-        )?;
-
         let expected1 = "pos=<x= 2, y=-1, z= 1>, vel=<x= 3, y=-1, z=-1>
 pos=<x= 3, y=-7, z=-4>, vel=<x= 1, y= 3, z= 3>
 pos=<x= 1, y=-7, z= 5>, vel=<x=-3, y= 1, z=-3>
@@ -291,33 +410,95 @@ pos=<x= 4, y= 1, z= 5>, vel=<x= 2, y= 0, z=-2>
 pos=<x= 2, y= 2, z=-4>, vel=<x= 0, y=-1, z= 2>
 pos=<x= 3, y=-7, z=-1>, vel=<x= 1, y= 2, z=-2>";
 
-        let moons1: Vec<Moon> = _parse_moon(expected1)?;
-        let mut moons = make_moons(&coors);
-        simulate(&mut moons, 1);
-        assert_eq!(moons, moons1);
+        let mut system = parse(SAMPLE)?;
+        system.step();
+        assert_eq!(system, parse(expected1)?);
 
-        let moons5: Vec<Moon> = _parse_moon(expected5)?;
-        let mut moons = make_moons(&coors);
-        simulate(&mut moons, 5);
-        assert_eq!(moons, moons5);
+        let mut system = parse(SAMPLE)?;
+        system.run(5);
+        assert_eq!(system, parse(expected5)?);
 
-        let mut moons = make_moons(&coors);
-        simulate(&mut moons, 10);
-        assert_eq!(moons.iter().map(|m| m.energy()).sum::<i32>(), 179);
+        let mut system = parse(SAMPLE)?;
+        system.run(10);
+        assert_eq!(system.energy(), 179);
         Ok(())
     }
 
     #[test]
-    fn test_part2() -> Result<()> {
+    fn test_dimensions() -> Result<()> {
+        let flat = parse("<x=-1, y=0>\n<x=2, y=-10>\n<x=4, y=-8>\n<x=3, y=5>")?;
+        assert_eq!(flat.periods(), vec![18, 28]);
+        assert_eq!(flat.period(), 252);
+
+        let four = parse(
+            "<x=-1, y=0, z=2, w=-1>
+<x=2, y=-10, z=-7, w=2>
+<x=4, y=-8, z=8, w=4>
+<x=3, y=5, z=-1, w=3>",
+        )?;
+        assert_eq!(four.periods(), vec![18, 28, 44, 18]);
+        assert_eq!(four.period(), 2772);
+
+        let alone = parse("<a=5>")?;
+        assert_eq!((alone.bodies(), alone.period()), (1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_modes() -> Result<()> {
+        let options = Options::parse(vec!["steps=10", "every=10"])?;
         assert_eq!(
-            part2(
-                "<x=-1, y=0, z=2>
-<x=2, y=-10, z=-7>
-<x=4, y=-8, z=8>
-<x=3, y=5, z=-1>",
-            )?,
-            2772
+            energy(SAMPLE, &options)?,
+            "step,potential,kinetic,total\n0,51,0,0\n10,31,22,179\n"
         );
+
+        let options = Options::parse(vec!["steps=1"])?;
+        let csv = trajectory(SAMPLE, &options)?;
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "step,body,x,y,z,vx,vy,vz");
+        assert_eq!(lines[1], "0,0,-1,0,2,0,0,0");
+        assert_eq!(lines[5], "1,0,2,-1,1,3,-1,-1");
+
+        let options = Options::parse(vec![])?;
+        assert_eq!(periods(SAMPLE, &options)?, "x: 18\ny: 28\nz: 44\nall: 2772");
+        Ok(())
+    }
+
+    #[test]
+    fn test_period_shortcuts() -> Result<()> {
+        let system = parse(SAMPLE)?;
+        let mut moving = system.clone();
+        moving.step();
+        let still: Vec<_> = [
+            vec![5],
+            vec![0, 3],
+            vec![1, 1, 4],
+            vec![-3, 7, 0, 2],
+            vec![4, -4, 9, 1, -6],
+        ]
+        .iter()
+        .map(|positions| Axis {
+            positions: positions.iter().map(|&p| Vector::new([p])).collect(),
+            velocities: vec![Vector::zero(); positions.len()],
+        })
+        .collect();
+        let (axes, moving) = (system.axes(), moving.axes());
+        for axis in axes.iter().chain(&moving).chain(&still) {
+            let expected = copying_period(axis);
+            assert_eq!(axis.full_period(), expected, "{:?}", axis);
+            assert_eq!(axis.period(), expected, "{:?}", axis);
+            if axis.still() {
+                assert_eq!(axis.half_period(), expected, "{:?}", axis);
+            }
+        }
+        assert_eq!(system.axis(0).half_period(), 18);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        assert_eq!(part2(SAMPLE)?, 2772);
         Ok(())
     }
 }
@@ -1,8 +1,9 @@
+use aoc2019::coor::Coor;
 use aoc2019::cpu::{set_memory, Cpu, CpuState};
+use aoc2019::grid::Grid;
 use aoc2019::{dispatch, Result};
 use failure::bail;
 use std::cmp::Ordering;
-use std::collections::HashMap;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -19,7 +20,7 @@ enum Tile {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum GameState {
-    Output(((i64, i64), Tile)),
+    Output((Coor, Tile)),
     Score(i64),
     NeedsInput,
     Halted,
@@ -48,7 +49,7 @@ fn tick(mut cpu: &mut Cpu) -> Result<GameState> {
                     4 => Tile::Ball,
                     t => bail!("invalid tile {} ({}, {})", t, x, y),
                 };
-                GameState::Output(((x, y), tile))
+                GameState::Output((Coor::new(x, y), tile))
             }
         }
         CpuState::NeedsInput => GameState::NeedsInput,
@@ -58,8 +59,8 @@ fn tick(mut cpu: &mut Cpu) -> Result<GameState> {
 }
 
 fn part1(input: &str) -> Result<usize> {
-    let mut cpu = Cpu::from_str(input);
-    let mut tiles = HashMap::new();
+    let mut cpu = Cpu::from_str(input)?;
+    let mut tiles = Grid::sparse();
     while let GameState::Output((pos, tile)) = tick(&mut cpu)? {
         tiles.insert(pos, tile);
     }
@@ -67,27 +68,21 @@ fn part1(input: &str) -> Result<usize> {
     Ok(tiles.values().filter(|&&t| t == Tile::Block).count())
 }
 
-fn _draw(tiles: &HashMap<(i64, i64), Tile>) {
-    let &(max_x, max_y) = tiles.keys().max().expect("empty");
-    let &(min_x, min_y) = tiles.keys().min().expect("empty");
-
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let c = match tiles.get(&(x, y)).unwrap_or(&Tile::Empty) {
-                Tile::Empty => ' ',
-                Tile::Wall => '#',
-                Tile::Block => 'X',
-                Tile::Paddle => '_',
-                Tile::Ball => '0',
-            };
-            print!("{}", c);
-        }
-        print!("\n");
-    }
+fn _draw(tiles: &Grid<Tile>) {
+    print!(
+        "{}",
+        tiles.render(|tile| match tile.unwrap_or(&Tile::Empty) {
+            Tile::Empty => ' ',
+            Tile::Wall => '#',
+            Tile::Block => 'X',
+            Tile::Paddle => '_',
+            Tile::Ball => '0',
+        })
+    );
 }
 
 fn part2(input: &str) -> Result<i64> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     set_memory(&mut cpu, 0, 2);
     // let mut tiles = HashMap::new();
     let mut ball_x = 0;
@@ -98,9 +93,9 @@ fn part2(input: &str) -> Result<i64> {
             GameState::Output((pos, tile)) => {
                 // tiles.insert(pos, tile);
                 if tile == Tile::Ball {
-                    ball_x = pos.0;
+                    ball_x = pos.x;
                 } else if tile == Tile::Paddle {
-                    paddle_x = pos.0;
+                    paddle_x = pos.x;
                 }
             }
             GameState::NeedsInput => {
@@ -1,71 +1,378 @@
-use aoc2019::{dispatch, Result};
-use failure::{err_msg, Error};
+use aoc2019::parse::{captures, field, lines, parse_nonblank_lines, shift, split_with, ParseError};
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::{bail, format_err, Error};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::cmp::max;
 use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(
+        &part1,
+        &part2,
+        &[
+            ("bom", &bom),
+            ("max", &max),
+            ("dot", &dot),
+            ("analyse", &analyse),
+        ],
+    )
 }
 
-fn make(available: &mut HashMap<String, i64>, recipe_map: &HashMap<String, Recipe>) -> Result<()> {
-    Ok(loop {
-        let needed: Vec<(String, i64)> = available
+const ORE: &str = "ORE";
+const FUEL: &str = "FUEL";
+
+/// One reaction's part in a `Plan`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Step {
+    chemical: String,
+    batches: i64,
+    produced: i64,
+    used: i64,
+}
+
+impl Step {
+    fn leftover(&self) -> i64 {
+        self.produced - self.used
+    }
+}
+
+/// Everything that has to happen to make some amount of a chemical.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Plan {
+    ore: i64,
+    /// Each chemical before the ones it's made from.
+    steps: Vec<Step>,
+}
+
+impl Plan {
+    /// Chemicals made but not used up, with the amount left.
+    fn leftovers(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.steps
             .iter()
-            .filter(|&(name, &amount)| name != "ORE" && amount < 0)
-            .map(|(name, amount)| (name.clone(), -*amount))
-            .collect();
-        if needed.len() == 0 {
-            break;
-        }
-        for (ingredient, amount) in needed {
-            let recipe = recipe_map
-                .get(&ingredient)
-                .ok_or(err_msg("ingredient not found"))?;
-            let multiple = max(amount / recipe.makes.amount, 1);
-            *available.entry(ingredient).or_insert(0) += recipe.makes.amount * multiple;
-            for ingredient in recipe.requires.iter() {
-                *available.entry(ingredient.name.clone()).or_insert(0) -=
-                    ingredient.amount * multiple;
-            }
+            .filter(|s| s.leftover() > 0)
+            .map(|s| (s.chemical.as_str(), s.leftover()))
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .steps
+            .iter()
+            .map(|s| s.chemical.len())
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{:w$}  {:>10}  {:>12}  {:>12}  {:>8}",
+            "chemical",
+            "batches",
+            "produced",
+            "used",
+            "leftover",
+            w = width.max(8)
+        )?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "{:w$}  {:>10}  {:>12}  {:>12}  {:>8}",
+                step.chemical,
+                step.batches,
+                step.produced,
+                step.used,
+                step.leftover(),
+                w = width.max(8)
+            )?;
         }
-    })
+        write!(f, "{} {}", self.ore, ORE)
+    }
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let recipe_map = parse(input)?;
-    let mut available: HashMap<String, i64> = HashMap::new();
-    available.insert("FUEL".into(), -1);
-    make(&mut available, &recipe_map)?;
-    available.get("ORE").ok_or(err_msg("no ore?")).map(|&n| -n)
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Visiting,
+    Done,
 }
 
-fn part2(input: &str) -> Result<i64> {
-    let recipe_map = parse(input)?;
-    let mut available: HashMap<String, i64> = HashMap::new();
-    available.insert("ORE".into(), 1000000000000);
-    let mut fuel = 0;
-    let mut scale = 1;
-    loop {
-        let prev = available.clone();
-        *available.entry("FUEL".into()).or_insert(0) -= scale;
-        make(&mut available, &recipe_map)?;
-
-        let ore = *available.get("ORE").ok_or(err_msg("no ore?"))?;
-        if ore < 0 {
-            if scale == 1 {
-                break Ok(fuel);
+/// The recipes, in an order that lets each chemical's needs be settled in
+/// one pass.
+struct Reactions {
+    recipes: HashMap<String, Recipe>,
+    /// Every chemical before the ones it's made from, ORE last.
+    order: Vec<String>,
+}
+
+impl Reactions {
+    fn new(recipes: HashMap<String, Recipe>) -> Result<Self> {
+        let mut marks = HashMap::new();
+        let mut order = vec![];
+        let mut names: Vec<_> = recipes.keys().collect();
+        names.sort();
+        for name in names {
+            Reactions::visit(&recipes, name, &mut marks, &mut order)?;
+        }
+        order.reverse();
+        Ok(Reactions { recipes, order })
+    }
+
+    /// Depth first, pushing `name` after everything it's made from.
+    fn visit<'a>(
+        recipes: &'a HashMap<String, Recipe>,
+        name: &'a str,
+        marks: &mut HashMap<&'a str, Mark>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        match marks.get(name) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => bail!("recipes for {} depend on each other", name),
+            None => {}
+        }
+        if name != ORE {
+            let recipe = recipes
+                .get(name)
+                .ok_or_else(|| format_err!("nothing makes {}", name))?;
+            marks.insert(name, Mark::Visiting);
+            for ingredient in &recipe.requires {
+                Reactions::visit(recipes, &ingredient.name, marks, order)?;
+            }
+        }
+        marks.insert(name, Mark::Done);
+        order.push(name.into());
+        Ok(())
+    }
+
+    fn plan(&self, target: &str, amount: i64) -> Result<Plan> {
+        self.checked_plan(target, amount)?
+            .ok_or_else(|| format_err!("{} {} takes too much to count", amount, target))
+    }
+
+    /// Like `plan`, with `None` when some amount doesn't fit in an i64.
+    fn checked_plan(&self, target: &str, amount: i64) -> Result<Option<Plan>> {
+        if target != ORE && !self.recipes.contains_key(target) {
+            bail!("nothing makes {}", target);
+        }
+        if amount < 1 {
+            bail!("can't make {} {}", amount, target);
+        }
+        let mut needed: HashMap<&str, i64> = HashMap::new();
+        needed.insert(target, amount);
+        let mut steps = vec![];
+        for chemical in &self.order {
+            let used = needed.get(chemical.as_str()).cloned().unwrap_or(0);
+            if used == 0 || chemical == ORE {
+                continue;
+            }
+            let recipe = &self.recipes[chemical];
+            let makes = recipe.makes.amount;
+            let batches = used / makes + i64::from(used % makes != 0);
+            for ingredient in &recipe.requires {
+                let total = needed.entry(&ingredient.name).or_insert(0);
+                match ingredient
+                    .amount
+                    .checked_mul(batches)
+                    .and_then(|more| total.checked_add(more))
+                {
+                    Some(sum) => *total = sum,
+                    None => return Ok(None),
+                }
+            }
+            let produced = match batches.checked_mul(makes) {
+                Some(produced) => produced,
+                None => return Ok(None),
+            };
+            steps.push(Step {
+                chemical: chemical.clone(),
+                batches,
+                produced,
+                used,
+            });
+        }
+        Ok(Some(Plan {
+            ore: needed.get(ORE).cloned().unwrap_or(0),
+            steps,
+        }))
+    }
+
+    /// Reactions between ORE and each chemical on the longest way there.
+    fn depths(&self) -> HashMap<&str, usize> {
+        let mut depths = HashMap::new();
+        for chemical in self.order.iter().rev() {
+            let depth = match self.recipes.get(chemical) {
+                None => 0,
+                Some(recipe) => {
+                    1 + recipe
+                        .requires
+                        .iter()
+                        .map(|i| depths[i.name.as_str()])
+                        .max()
+                        .unwrap_or(0)
+                }
+            };
+            depths.insert(chemical.as_str(), depth);
+        }
+        depths
+    }
+
+    /// The longest way from `target` down to ORE, both included, ties going
+    /// to the first ingredient listed.
+    fn critical_chain(&self, target: &str) -> Vec<&str> {
+        let depths = self.depths();
+        let mut chain = vec![];
+        let mut current = self
+            .recipes
+            .get_key_value(target)
+            .map(|(name, _)| name.as_str());
+        while let Some(chemical) = current {
+            chain.push(chemical);
+            current = self.recipes.get(chemical).and_then(|recipe| {
+                let deepest = recipe
+                    .requires
+                    .iter()
+                    .map(|i| depths[i.name.as_str()])
+                    .max()?;
+                recipe
+                    .requires
+                    .iter()
+                    .find(|i| depths[i.name.as_str()] == deepest)
+                    .map(|i| i.name.as_str())
+            });
+        }
+        chain
+    }
+
+    /// Graphviz, with an edge from each ingredient to what it's used for,
+    /// labelled with the amount one batch needs.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph reactions {\n");
+        for chemical in &self.order {
+            match self.recipes.get(chemical) {
+                None => dot += &format!("    \"{}\" [shape=box];\n", chemical),
+                Some(recipe) => {
+                    dot += &format!(
+                        "    \"{}\" [label=\"{} ({})\"];\n",
+                        chemical, chemical, recipe.makes.amount
+                    );
+                }
+            }
+        }
+        for chemical in &self.order {
+            if let Some(recipe) = self.recipes.get(chemical) {
+                for ingredient in &recipe.requires {
+                    dot += &format!(
+                        "    \"{}\" -> \"{}\" [label=\"{}\", weight={}];\n",
+                        ingredient.name, chemical, ingredient.amount, ingredient.amount
+                    );
+                }
+            }
+        }
+        dot + "}"
+    }
+
+    fn ore_for(&self, target: &str, amount: i64) -> Result<i64> {
+        self.plan(target, amount).map(|plan| plan.ore)
+    }
+
+    /// The most of `target` that `ore` is enough for.
+    fn max_output(&self, target: &str, ore: i64) -> Result<i64> {
+        if self.ore_for(target, 1)? == 0 {
+            bail!("{} doesn't need any {}", target, ORE);
+        }
+        let fits = |amount| -> Result<bool> {
+            let plan = self.checked_plan(target, amount)?;
+            Ok(plan.is_some_and(|plan| plan.ore <= ore))
+        };
+        // can make `low`, can't make `high`
+        let mut low = 0;
+        let mut high = 1;
+        while fits(high)? {
+            if high == i64::MAX {
+                return Ok(high);
+            }
+            low = high;
+            high = high.checked_mul(2).unwrap_or(i64::MAX);
+        }
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if fits(middle)? {
+                low = middle;
             } else {
-                available = prev;
-                scale /= 10;
+                high = middle;
             }
-        } else {
-            fuel += scale;
-            scale *= 10;
         }
+        Ok(low)
+    }
+}
+
+/// The bill of materials for `-o amount=N` of `-o target=NAME`.
+fn bom(input: &str, options: &Options) -> Result<String> {
+    let reactions = Reactions::new(parse(input)?)?;
+    let target: String = options.get("target", FUEL.into())?;
+    let plan = reactions.plan(&target, options.get("amount", 1)?)?;
+    let leftovers: Vec<_> = plan
+        .leftovers()
+        .map(|(chemical, amount)| format!("{} {}", amount, chemical))
+        .collect();
+    if leftovers.is_empty() {
+        return Ok(format!("{}\nnothing left over", plan));
+    }
+    Ok(format!("{}\nleftover: {}", plan, leftovers.join(", ")))
+}
+
+/// How much of `-o target=NAME` `-o ore=N` is enough for.
+fn max(input: &str, options: &Options) -> Result<String> {
+    let reactions = Reactions::new(parse(input)?)?;
+    let target: String = options.get("target", FUEL.into())?;
+    let ore = options.get("ore", 1_000_000_000_000)?;
+    Ok(reactions.max_output(&target, ore)?.to_string())
+}
+
+/// The reactions as a Graphviz digraph.
+fn dot(input: &str, _options: &Options) -> Result<String> {
+    Ok(Reactions::new(parse(input)?)?.to_dot())
+}
+
+/// Depths, the critical chain and the waste of making `-o fuel=N` FUEL.
+fn analyse(input: &str, options: &Options) -> Result<String> {
+    let reactions = Reactions::new(parse(input)?)?;
+    let fuel = options.get("fuel", 1)?;
+    let plan = reactions.plan(FUEL, fuel)?;
+    let depths = reactions.depths();
+    let mut report = format!(
+        "{:10}  {:>5}  {:>12}  {:>12}  {:>6}\n",
+        "chemical", "depth", "produced", "wasted", "waste%"
+    );
+    for step in &plan.steps {
+        report += &format!(
+            "{:10}  {:>5}  {:>12}  {:>12}  {:>6.2}\n",
+            step.chemical,
+            depths[step.chemical.as_str()],
+            step.produced,
+            step.leftover(),
+            100.0 * step.leftover() as f64 / step.produced as f64
+        );
     }
+    let chain = reactions.critical_chain(FUEL);
+    report += &format!(
+        "critical chain ({} reactions): {}\n",
+        chain.len() - 1,
+        chain.join(" <- ")
+    );
+    let wasted: i64 = plan.steps.iter().map(Step::leftover).sum();
+    report += &format!(
+        "{} {} for {} {}, {} units wasted",
+        plan.ore, ORE, fuel, FUEL, wasted
+    );
+    Ok(report)
+}
+
+fn part1(input: &str) -> Result<i64> {
+    Reactions::new(parse(input)?)?.ore_for(FUEL, 1)
+}
+
+fn part2(input: &str) -> Result<i64> {
+    Reactions::new(parse(input)?)?.max_output(FUEL, 1_000_000_000_000)
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -81,14 +388,18 @@ impl FromStr for Ingredient {
         lazy_static! {
             static ref RE: Regex =
                 // 13 CA
-                Regex::new(r"(\d+) (\w+)")
+                Regex::new(r"^(\d+) (\w+)$")
                     .expect("regex create");
         }
 
-        let caps = RE.captures(s).ok_or(err_msg("no match"))?;
+        let caps = captures(&RE, s)?;
+        let amount = field(&caps, 1)?;
+        if amount == 0 {
+            return Err(ParseError::new(1, 1, "amounts have to be positive").into());
+        }
         Ok(Self {
-            amount: caps[1].parse()?,
-            name: caps[2].parse()?,
+            amount,
+            name: field(&caps, 2)?,
         })
     }
 }
@@ -103,58 +414,32 @@ impl FromStr for Recipe {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        lazy_static! {
-            static ref RE1: Regex =
-                // 3 A, 4 B => 1 AB
-                Regex::new(r"(?P<ingredients>((?P<ingredient>[^,]+)(, )?)+) => (?P<result>.*)")
-                    .expect("regex create");
-        }
-        lazy_static! {
-            static ref RE2: Regex =
-                // 3 A, 4 B
-                Regex::new(r"(?P<ingredient>[^,]+)(, )?")
-                    .expect("regex create");
-        }
+        // 3 A, 4 B => 1 AB
+        let arrow = " => ";
+        let split = s
+            .find(arrow)
+            .ok_or_else(|| ParseError::new(1, 1, "missing =>"))?;
+        let requires = split_with(&s[..split], ", ", str::parse)?;
+        let makes = shift(s[split + arrow.len()..].parse(), split + arrow.len())?;
 
-        let caps1 = RE1.captures(s).ok_or(err_msg("no regex match"))?;
-
-        let ingredients: Vec<_> = RE2
-            .captures_iter(
-                caps1
-                    .name("ingredients")
-                    .ok_or(err_msg("ingredients parse fail"))?
-                    .as_str(),
-            )
-            .map(|c| {
-                c.name("ingredient")
-                    .expect("ingredient parse fail")
-                    .as_str()
-                    .parse::<Ingredient>()
-                    .expect("can't make ingredient")
-            })
-            .collect();
-        let result = caps1
-            .name("result")
-            .ok_or(err_msg("result parse fail"))?
-            .as_str()
-            .parse::<Ingredient>()?;
-
-        Ok(Recipe {
-            makes: result,
-            requires: ingredients,
-        })
+        Ok(Recipe { makes, requires })
     }
 }
 
 fn parse(input: &str) -> Result<HashMap<String, Recipe>> {
-    let recipes = input
-        .split("\n")
-        .map(|row| row.parse::<Recipe>())
-        .collect::<Result<Vec<_>>>()?;
-    Ok(recipes
-        .iter()
-        .map(|recipe| (recipe.makes.name.clone(), recipe.clone()))
-        .collect())
+    let recipes: Vec<Recipe> = parse_nonblank_lines(input, str::parse)?;
+    let numbers = lines(input)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, _)| number);
+    let mut recipe_map = HashMap::new();
+    for (number, recipe) in numbers.zip(recipes) {
+        let name = recipe.makes.name.clone();
+        if recipe_map.insert(name.clone(), recipe).is_some() {
+            let message = format!("another recipe already makes {}", name);
+            return Err(ParseError::new(number, 1, &message).into());
+        }
+    }
+    Ok(recipe_map)
 }
 
 #[cfg(test)]
@@ -185,6 +470,117 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = parse("10 ORE => 10 A\n7 A, 1 => 1 FUEL")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!((e.line, e.column), (2, 6));
+
+        let e = parse("\n\n10 ORE => 10 A\n\n7 A, 1 => 1 FUEL\n")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!((e.line, e.column), (5, 6));
+
+        for (input, column) in &[("1 ORE => 0 A", 10), ("3 B, 0 ORE => 1 A", 6)] {
+            let e = parse(&format!("1 ORE => 1 B\n{}", input))
+                .expect_err("zero amount")
+                .downcast::<ParseError>()
+                .expect("ParseError");
+            assert_eq!((e.line, e.column), (2, *column));
+        }
+    }
+
+    #[test]
+    fn test_plan() -> Result<()> {
+        let reactions = Reactions::new(parse(
+            "10 ORE => 10 A
+1 ORE => 1 B
+7 A, 1 B => 1 C
+7 A, 1 C => 1 D
+7 A, 1 D => 1 E
+7 A, 1 E => 1 FUEL",
+        )?)?;
+        let plan = reactions.plan(FUEL, 1)?;
+        assert_eq!(plan.ore, 31);
+        let chemicals: Vec<_> = plan.steps.iter().map(|s| s.chemical.as_str()).collect();
+        assert_eq!(chemicals, vec!["FUEL", "E", "D", "C", "B", "A"]);
+        assert_eq!(plan.leftovers().collect::<Vec<_>>(), vec![("A", 2)]);
+        assert_eq!(reactions.ore_for("C", 2)?, 2 * 10 + 2);
+        assert_eq!(reactions.ore_for(ORE, 5)?, 5);
+        assert_eq!(reactions.max_output(FUEL, 30)?, 0);
+        assert_eq!(reactions.max_output(FUEL, 31)?, 1);
+        assert_eq!(reactions.max_output("B", 1000)?, 1000);
+
+        assert_eq!(reactions.max_output("B", i64::MAX)?, i64::MAX);
+        let most = reactions.max_output(FUEL, i64::MAX)?;
+        assert!(most > i64::MAX / 32 && reactions.plan(FUEL, most + 1).is_err());
+        assert!(reactions.plan(FUEL, 0).is_err());
+        assert!(bom("1 ORE => 1 FUEL", &Options::parse(vec!["amount=-3"])?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph() -> Result<()> {
+        let reactions = Reactions::new(parse(
+            "9 ORE => 2 A
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL",
+        )?)?;
+        let depths = reactions.depths();
+        assert_eq!(
+            (depths["ORE"], depths["A"], depths["BC"], depths["FUEL"]),
+            (0, 1, 2, 3)
+        );
+        assert_eq!(
+            reactions.critical_chain(FUEL),
+            vec!["FUEL", "AB", "A", "ORE"]
+        );
+        assert_eq!(reactions.critical_chain("C"), vec!["C", "ORE"]);
+
+        let dot = reactions.to_dot();
+        assert!(dot.starts_with("digraph reactions {\n"));
+        assert!(dot.contains("    \"ORE\" [shape=box];\n"));
+        assert!(dot.contains("    \"CA\" -> \"FUEL\" [label=\"4\", weight=4];\n"));
+        assert_eq!(dot.matches(" -> ").count(), 12);
+        let input = "2 ORE => 3 FUEL";
+        let report = analyse(input, &Options::parse(vec!["fuel=4"])?)?;
+        assert!(report.ends_with("4 ORE for 4 FUEL, 2 units wasted"));
+        assert!(analyse(input, &Options::parse(vec!["fuel=0"])?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_recipes() -> Result<()> {
+        let cycle = Reactions::new(parse("1 ORE, 1 B => 1 A\n1 A => 1 B\n1 A => 1 FUEL")?);
+        assert!(cycle.is_err());
+        let missing = Reactions::new(parse("1 ORE, 1 X => 1 A\n1 A => 1 FUEL")?);
+        assert_eq!(
+            missing.err().map(|e| e.to_string()),
+            Some("nothing makes X".into())
+        );
+        let reactions = Reactions::new(parse("1 ORE => 1 FUEL")?)?;
+        assert!(reactions.plan("A", 1).is_err());
+
+        let e = parse("1 ORE => 1 A\n2 ORE => 1 A")
+            .expect_err("duplicate")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!(e.line, 2);
+        let e = parse("\n1 ORE => 1 A\n\n2 ORE => 1 A")
+            .expect_err("duplicate")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!(e.line, 4);
+        Ok(())
+    }
+
     #[test]
     fn test_part1() -> Result<()> {
         assert_eq!(
@@ -205,12 +601,12 @@ mod tests {
         assert_eq!(
             part1(
                 "9 ORE => 2 A
-    8 ORE => 3 B
-    7 ORE => 5 C
-    3 A, 4 B => 1 AB
-    5 B, 7 C => 1 BC
-    4 C, 1 A => 1 CA
-    2 AB, 3 BC, 4 CA => 1 FUEL"
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL"
             )?,
             165
         );
@@ -222,17 +618,17 @@ mod tests {
         assert_eq!(
             part1(
                 "2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
-    17 NVRVD, 3 JNWZP => 8 VPVL
-    53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
-    22 VJHF, 37 MNCFX => 5 FWMGM
-    139 ORE => 4 NVRVD
-    144 ORE => 7 JNWZP
-    5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
-    5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
-    145 ORE => 6 MNCFX
-    1 NVRVD => 8 CXFTF
-    1 VJHF, 6 MNCFX => 4 RFSQX
-    176 ORE => 6 VJHF"
+17 NVRVD, 3 JNWZP => 8 VPVL
+53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
+22 VJHF, 37 MNCFX => 5 FWMGM
+139 ORE => 4 NVRVD
+144 ORE => 7 JNWZP
+5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
+5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
+145 ORE => 6 MNCFX
+1 NVRVD => 8 CXFTF
+1 VJHF, 6 MNCFX => 4 RFSQX
+176 ORE => 6 VJHF"
             )?,
             180697
         );
@@ -244,63 +640,44 @@ mod tests {
         assert_eq!(
             part1(
                 "171 ORE => 8 CNZTR
-    7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
-    114 ORE => 4 BHXH
-    14 VRPVC => 6 BMBT
-    6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
-    6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
-    15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
-    13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
-    5 BMBT => 4 WPTQ
-    189 ORE => 9 KTJDG
-    1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
-    12 VRPVC, 27 CNZTR => 2 XDBXC
-    15 KTJDG, 12 BHXH => 5 XCVML
-    3 BHXH, 2 VRPVC => 7 MZWV
-    121 ORE => 7 VRPVC
-    7 XCVML => 6 RJRHP
-    5 BHXH, 4 VRPVC => 5 LTCX"
+7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
+114 ORE => 4 BHXH
+14 VRPVC => 6 BMBT
+6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
+6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
+15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
+13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
+5 BMBT => 4 WPTQ
+189 ORE => 9 KTJDG
+1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
+12 VRPVC, 27 CNZTR => 2 XDBXC
+15 KTJDG, 12 BHXH => 5 XCVML
+3 BHXH, 2 VRPVC => 7 MZWV
+121 ORE => 7 VRPVC
+7 XCVML => 6 RJRHP
+5 BHXH, 4 VRPVC => 5 LTCX"
             )?,
             2210736
         );
         Ok(())
     }
 
-    #[test]
-    fn test_part2a() -> Result<()> {
-        assert_eq!(
-            part2(
-                "157 ORE => 5 NZVS
-    165 ORE => 6 DCFZ
-    44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
-    12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
-    179 ORE => 7 PSHF
-    177 ORE => 5 HKGWZ
-    7 DCFZ, 7 PSHF => 2 XJWVT
-    165 ORE => 2 GPVTF
-    3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT"
-            )?,
-            82892753
-        );
-        Ok(())
-    }
-
     #[test]
     fn test_part2b() -> Result<()> {
         assert_eq!(
             part2(
                 "2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
-    17 NVRVD, 3 JNWZP => 8 VPVL
-    53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
-    22 VJHF, 37 MNCFX => 5 FWMGM
-    139 ORE => 4 NVRVD
-    144 ORE => 7 JNWZP
-    5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
-    5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
-    145 ORE => 6 MNCFX
-    1 NVRVD => 8 CXFTF
-    1 VJHF, 6 MNCFX => 4 RFSQX
-    176 ORE => 6 VJHF"
+17 NVRVD, 3 JNWZP => 8 VPVL
+53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
+22 VJHF, 37 MNCFX => 5 FWMGM
+139 ORE => 4 NVRVD
+144 ORE => 7 JNWZP
+5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
+5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
+145 ORE => 6 MNCFX
+1 NVRVD => 8 CXFTF
+1 VJHF, 6 MNCFX => 4 RFSQX
+176 ORE => 6 VJHF"
             )?,
             5586022
         );
@@ -312,22 +689,22 @@ mod tests {
         assert_eq!(
             part2(
                 "171 ORE => 8 CNZTR
-    7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
-    114 ORE => 4 BHXH
-    14 VRPVC => 6 BMBT
-    6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
-    6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
-    15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
-    13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
-    5 BMBT => 4 WPTQ
-    189 ORE => 9 KTJDG
-    1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
-    12 VRPVC, 27 CNZTR => 2 XDBXC
-    15 KTJDG, 12 BHXH => 5 XCVML
-    3 BHXH, 2 VRPVC => 7 MZWV
-    121 ORE => 7 VRPVC
-    7 XCVML => 6 RJRHP
-    5 BHXH, 4 VRPVC => 5 LTCX"
+7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
+114 ORE => 4 BHXH
+14 VRPVC => 6 BMBT
+6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
+6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
+15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
+13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
+5 BMBT => 4 WPTQ
+189 ORE => 9 KTJDG
+1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
+12 VRPVC, 27 CNZTR => 2 XDBXC
+15 KTJDG, 12 BHXH => 5 XCVML
+3 BHXH, 2 VRPVC => 7 MZWV
+121 ORE => 7 VRPVC
+7 XCVML => 6 RJRHP
+5 BHXH, 4 VRPVC => 5 LTCX"
             )?,
             460664
         );
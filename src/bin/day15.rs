@@ -1,10 +1,10 @@
+use aoc2019::coor::{Coor, Direction};
 use aoc2019::cpu::{Cpu, CpuState};
+use aoc2019::search::{bfs, Outcome};
 use aoc2019::{dispatch, Result};
-use failure::{bail, Error};
-use std::collections::HashMap;
+use failure::{bail, err_msg, Error};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
-use std::fmt;
-use std::ops::Add;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -32,77 +32,19 @@ impl TryFrom<i64> for Tile {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
-    North,
-    South,
-    West,
-    East,
-}
-
-impl Direction {
-    fn opposite(&self) -> Self {
-        use Direction::*;
-        match self {
-            North => South,
-            South => North,
-            West => East,
-            East => West,
-        }
-    }
-    fn as_input(&self) -> i64 {
-        use Direction::*;
-        match self {
-            North => 1,
-            South => 2,
-            West => 3,
-            East => 4,
-        }
-    }
-
-    fn as_coor(&self) -> Coor {
-        use Direction::*;
-        match self {
-            North => Coor::new(0, -1),
-            South => Coor::new(0, 1),
-            West => Coor::new(-1, 0),
-            East => Coor::new(1, 0),
-        }
-    }
-
-    fn all() -> [Direction; 4] {
-        use Direction::*;
-        [North, South, West, East]
-    }
-}
-
-#[derive(PartialEq, Eq, Default, Clone, Copy, Hash)]
-struct Coor {
-    x: i32,
-    y: i32,
-}
-
-impl Coor {
-    fn new(x: i32, y: i32) -> Self {
-        Coor { x, y }
-    }
-}
-impl fmt::Debug for Coor {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
-
-impl Add for Coor {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Coor::new(self.x + other.x, self.y + other.y)
+/// The droid's movement command for `direction`.
+fn command(direction: Direction) -> i64 {
+    use Direction::*;
+    match direction {
+        Up => 1,
+        Down => 2,
+        Left => 3,
+        Right => 4,
     }
 }
 
 fn step(cpu: &mut Cpu, direction: Direction) -> Result<Tile> {
-    cpu.enqueue_input(direction.as_input());
+    cpu.enqueue_input(command(direction));
     let state = cpu.run()?;
     match state {
         CpuState::Output(value) => Tile::try_from(value),
@@ -110,24 +52,24 @@ fn step(cpu: &mut Cpu, direction: Direction) -> Result<Tile> {
     }
 }
 
-fn map(input: &str) -> Result<(HashMap<Coor, Tile>, Coor, usize)> {
+fn map(input: &str) -> Result<(HashMap<Coor, Tile>, Coor)> {
     use Tile::*;
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     let mut queue = vec![];
     let start = Coor::default();
-    for direction in &Direction::all() {
+    for direction in &Direction::ALL {
         queue.push((start, *direction, 1));
     }
     let mut tried = HashMap::new();
     tried.insert(start, Tile::Open);
     let mut path: Vec<(Direction, Coor)> = vec![];
-    let mut oxygen: Option<(Coor, usize)> = None;
+    let mut oxygen = None;
     loop {
         let (pos, direction, distance) = match queue.pop() {
             Some(entry) => entry,
             None => {
                 if let Some(target) = oxygen {
-                    return Ok((tried, target.0, target.1));
+                    return Ok((tried, target));
                 } else {
                     bail!("didn't find oxygen");
                 }
@@ -142,18 +84,18 @@ fn map(input: &str) -> Result<(HashMap<Coor, Tile>, Coor, usize)> {
             };
         }
 
-        let next = pos + direction.as_coor();
+        let next = pos + direction.offset();
 
         let response = step(&mut cpu, direction)?;
         tried.insert(next, response);
         if response == Oxygen {
-            oxygen = Some((next, distance));
+            oxygen = Some(next);
         }
         match response {
             Open | Oxygen => {
                 path.push((direction, next));
-                for direction in &Direction::all() {
-                    if tried.contains_key(&(next + direction.as_coor())) {
+                for direction in &Direction::ALL {
+                    if tried.contains_key(&(next + direction.offset())) {
                         continue;
                     }
                     queue.push((next, *direction, distance + 1));
@@ -164,36 +106,34 @@ fn map(input: &str) -> Result<(HashMap<Coor, Tile>, Coor, usize)> {
     }
 }
 
+/// Breadth first over the explored map.
+fn flood(tiles: &HashMap<Coor, Tile>, from: Coor, to: Option<Coor>) -> Outcome<Coor, usize> {
+    bfs(
+        from,
+        HashSet::new(),
+        |&pos| {
+            pos.neighbours4()
+                .filter(|n| tiles.get(n).is_some_and(|&t| t != Tile::Wall))
+                .collect::<Vec<_>>()
+        },
+        |&pos| Some(pos) == to,
+    )
+}
+
 fn part1(input: &str) -> Result<usize> {
-    map(input).map(|(_, _, d)| d)
+    let (tiles, oxygen) = map(input)?;
+    flood(&tiles, Coor::default(), Some(oxygen))
+        .cost()
+        .ok_or_else(|| err_msg("no path to oxygen"))
 }
 
-fn part2(input: &str) -> Result<i32> {
-    use Tile::*;
-    let (mut tiles, oxygen, _) = map(input)?;
-    let mut minutes = 0;
-    let mut new_oxygen = vec![oxygen];
-    loop {
-        let mut next_oxygen = vec![];
-        for &coor in new_oxygen.iter() {
-            for direction in &Direction::all() {
-                let neighbour = coor + direction.as_coor();
-                match tiles.get(&neighbour) {
-                    Some(Open) => {
-                        tiles.insert(neighbour, Oxygen);
-                        next_oxygen.push(neighbour);
-                    }
-                    _ => {}
-                }
-            }
-        }
-        new_oxygen = next_oxygen;
-        if new_oxygen.len() == 0 {
-            break;
-        }
-        minutes += 1;
-    }
-    Ok(minutes)
+fn part2(input: &str) -> Result<usize> {
+    let (tiles, oxygen) = map(input)?;
+    Ok(flood(&tiles, oxygen, None)
+        .reached()
+        .map(|(_, minutes)| minutes)
+        .max()
+        .unwrap_or(0))
 }
 
 #[cfg(test)]
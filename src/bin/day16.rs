@@ -1,15 +1,16 @@
-use aoc2019::{dispatch, Result};
+use aoc2019::parse::digits;
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::{bail, Error};
+use std::str::FromStr;
+use std::thread;
+use std::time::Instant;
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(&part1, &part2, &[("fft", &fft_mode), ("bench", &bench)])
 }
 
-fn parse(input: &str) -> Vec<i64> {
-    input
-        .chars()
-        .map(|c| c as i64 - '0' as i64)
-        .filter(|&d| d >= 0 && d <= 9)
-        .collect()
+fn parse(input: &str) -> Result<Vec<i64>> {
+    digits(input)
 }
 
 // 1 1 1 0 0 0 -1 -1 -1 0 0 0 1 1 1 0 0 0
@@ -48,12 +49,193 @@ fn round(digits: Vec<i64>) -> Vec<i64> {
     result
 }
 
-fn part1(input: &str) -> Result<usize> {
-    let mut digits = parse(input);
-    for _ in 0..100 {
-        digits = round(digits);
+/// The pattern blocks each output digit sums, from prefix sums of the digits
+/// from `offset` on. O(n log n), for any offset.
+fn round_prefix(tail: &[i64], offset: usize) -> Vec<i64> {
+    let len = offset + tail.len();
+    let mut prefix = Vec::with_capacity(tail.len() + 1);
+    prefix.push(0);
+    for (idx, digit) in tail.iter().enumerate() {
+        prefix.push(prefix[idx] + digit);
+    }
+    (offset..len)
+        .map(|idx| {
+            let repeat = idx + 1;
+            let mut sum = 0;
+            let mut sign = 1;
+            let mut start = idx;
+            while start < len {
+                let end = (start + repeat).min(len);
+                sum += sign * (prefix[end - offset] - prefix[start - offset]);
+                sign = -sign;
+                start += 2 * repeat;
+            }
+            (sum % 10).abs()
+        })
+        .collect()
+}
+
+/// `round_prefix` on small integers, reusing its buffers between phases and
+/// sharing each phase's digits between threads.
+struct Engine {
+    threads: usize,
+    offset: usize,
+    current: Vec<i8>,
+    next: Vec<i8>,
+    prefix: Vec<i32>,
+}
+
+impl Engine {
+    /// Digits handed out to threads at a time. The first digits sum the most
+    /// blocks, so every thread gets some from all over the signal.
+    const CHUNK: usize = 4096;
+
+    fn new(tail: &[i64], offset: usize, threads: usize) -> Self {
+        Engine {
+            threads: threads.max(1),
+            offset,
+            current: tail.iter().map(|&d| d as i8).collect(),
+            next: vec![0; tail.len()],
+            prefix: vec![0; tail.len() + 1],
+        }
+    }
+
+    fn digit(prefix: &[i32], offset: usize, idx: usize) -> i8 {
+        let len = offset + prefix.len() - 1;
+        let repeat = idx + 1;
+        let mut sum = 0;
+        let mut start = idx;
+        while start < len {
+            sum += prefix[(start + repeat).min(len) - offset] - prefix[start - offset];
+            start += 2 * repeat;
+            if start >= len {
+                break;
+            }
+            sum -= prefix[(start + repeat).min(len) - offset] - prefix[start - offset];
+            start += 2 * repeat;
+        }
+        (sum % 10).abs() as i8
+    }
+
+    fn phase(&mut self) {
+        for (idx, &digit) in self.current.iter().enumerate() {
+            self.prefix[idx + 1] = self.prefix[idx] + digit as i32;
+        }
+        let (prefix, offset, threads) = (&self.prefix[..], self.offset, self.threads);
+        let fill = |share: Vec<(usize, &mut [i8])>| {
+            for (start, chunk) in share {
+                for (idx, digit) in chunk.iter_mut().enumerate() {
+                    *digit = Engine::digit(prefix, offset, start + idx);
+                }
+            }
+        };
+        let mut shares: Vec<Vec<(usize, &mut [i8])>> = (0..threads).map(|_| vec![]).collect();
+        for (idx, chunk) in self.next.chunks_mut(Engine::CHUNK).enumerate() {
+            shares[idx % threads].push((offset + idx * Engine::CHUNK, chunk));
+        }
+        shares.retain(|share| !share.is_empty());
+        if shares.len() <= 1 {
+            shares.into_iter().for_each(fill);
+        } else {
+            thread::scope(|scope| {
+                for share in shares {
+                    scope.spawn(move || fill(share));
+                }
+            });
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    fn run(mut self, phases: usize) -> Vec<i64> {
+        for _ in 0..phases {
+            self.phase();
+        }
+        self.current.into_iter().map(i64::from).collect()
+    }
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Strategy {
+    /// `round`, O(n²).
+    Naive,
+    /// `round_prefix`.
+    Prefix,
+    /// `round2`, only right from the middle of the signal on.
+    Suffix,
+    /// `Engine` with this many threads.
+    Parallel(usize),
+}
+
+impl Strategy {
+    /// The fastest one that works from `offset` in a signal of `len` digits.
+    fn best(offset: usize, len: usize) -> Self {
+        if offset * 2 >= len {
+            Strategy::Suffix
+        } else {
+            Strategy::Prefix
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "naive" => Strategy::Naive,
+            "prefix" => Strategy::Prefix,
+            "suffix" => Strategy::Suffix,
+            "parallel" => Strategy::Parallel(default_threads()),
+            _ => bail!("unknown strategy {:?}", s),
+        })
+    }
+}
+
+/// The digits from `offset` on after `phases` phases. Later digits never
+/// depend on earlier ones, so those are never worked out, except by `Naive`.
+fn fft(digits: &[i64], offset: usize, phases: usize, strategy: Strategy) -> Result<Vec<i64>> {
+    if offset > digits.len() {
+        bail!("offset {} is past the end of the signal", offset);
+    }
+    match strategy {
+        Strategy::Naive => {
+            let mut digits = digits.to_vec();
+            for _ in 0..phases {
+                digits = round(digits);
+            }
+            Ok(digits.split_off(offset))
+        }
+        Strategy::Prefix => {
+            let mut tail = digits[offset..].to_vec();
+            for _ in 0..phases {
+                tail = round_prefix(&tail, offset);
+            }
+            Ok(tail)
+        }
+        Strategy::Suffix => {
+            if offset * 2 < digits.len() {
+                bail!("the suffix strategy needs an offset in the second half");
+            }
+            let mut tail = digits[offset..].to_vec();
+            for _ in 0..phases {
+                tail = round2(tail);
+            }
+            Ok(tail)
+        }
+        Strategy::Parallel(threads) => {
+            Ok(Engine::new(&digits[offset..], offset, threads).run(phases))
+        }
     }
-    Ok(to_number(&digits[..8]))
+}
+
+fn part1(input: &str) -> Result<usize> {
+    let digits = parse(input)?;
+    let output = fft(&digits, 0, 100, Strategy::Prefix)?;
+    Ok(to_number(&output[..8]))
 }
 
 fn to_number(digits: &[i64]) -> usize {
@@ -79,15 +261,90 @@ fn round2(digits: Vec<i64>) -> Vec<i64> {
     result
 }
 
-fn part2(input: &str) -> Result<usize> {
-    let digits = parse(input);
-    let start = to_number(&digits[..7]);
-    let total = digits.len() * 10_000;
-    let mut tail_digits: Vec<i64> = (start..total).map(|i| value(&digits, i)).collect();
-    for _ in 0..100 {
-        tail_digits = round2(tail_digits);
+/// The offset of the message, from the first 7 digits.
+fn message_offset(digits: &[i64]) -> Result<usize> {
+    if digits.len() < 7 {
+        bail!("the message offset needs 7 digits, got {}", digits.len());
+    }
+    Ok(to_number(&digits[..7]))
+}
+
+fn repeated(digits: &[i64], times: usize) -> Vec<i64> {
+    (0..digits.len() * times)
+        .map(|i| value(digits, i))
+        .collect()
+}
+
+/// The 8 digits at `-o offset=N` after `-o phases=N` phases of the signal
+/// repeated `-o repeat=N` times, using `-o strategy=naive|prefix|suffix`. The
+/// offset defaults to the message offset when the signal is repeated.
+fn fft_mode(input: &str, options: &Options) -> Result<String> {
+    let digits = parse(input)?;
+    let times = options.get("repeat", 1)?;
+    let default_offset = if times > 1 {
+        message_offset(&digits)?
+    } else {
+        0
+    };
+    let offset = options.get("offset", default_offset)?;
+    let signal = repeated(&digits, times);
+    let strategy = match options.get_str("strategy") {
+        Some("parallel") => Strategy::Parallel(options.get("threads", default_threads())?),
+        Some(name) => name.parse()?,
+        None => Strategy::best(offset, signal.len()),
+    };
+    let output = fft(&signal, offset, options.get("phases", 100)?, strategy)?;
+    Ok(output.iter().take(8).map(|d| d.to_string()).collect())
+}
+
+/// Times `-o phases=N` phases of each strategy, on the input and from the
+/// start and the message offset of the input repeated `-o repeat=N` times.
+/// The whole repeated signal takes a while without many cores.
+fn bench(input: &str, options: &Options) -> Result<String> {
+    let digits = parse(input)?;
+    let phases = options.get("phases", 100)?;
+    let threads = options.get("threads", default_threads())?;
+    let signal = repeated(&digits, options.get("repeat", 10_000)?);
+    let message = message_offset(&digits)?;
+
+    let mut runs = vec![
+        (&digits, 0, Strategy::Naive),
+        (&digits, 0, Strategy::Prefix),
+        (&digits, 0, Strategy::Parallel(threads)),
+        (&signal, 0, Strategy::Prefix),
+        (&signal, 0, Strategy::Parallel(threads)),
+    ];
+    if message < signal.len() {
+        runs.push((&signal, message, Strategy::Prefix));
+        runs.push((&signal, message, Strategy::Parallel(threads)));
+        if message * 2 >= signal.len() {
+            runs.push((&signal, message, Strategy::Suffix));
+        }
+    }
+
+    let mut report = vec![];
+    for (signal, offset, strategy) in runs {
+        let start = Instant::now();
+        let output = fft(signal, offset, phases, strategy)?;
+        let first: String = output.iter().take(8).map(|d| d.to_string()).collect();
+        report.push(format!(
+            "{:>8} digits from {:>7}  {:14}  {:>10.1?}  {}",
+            signal.len(),
+            offset,
+            format!("{:?}", strategy),
+            start.elapsed(),
+            first
+        ));
     }
-    Ok(to_number(&tail_digits[..8]))
+    Ok(report.join("\n"))
+}
+
+fn part2(input: &str) -> Result<usize> {
+    let digits = parse(input)?;
+    let start = message_offset(&digits)?;
+    let signal = repeated(&digits, 10_000);
+    let output = fft(&signal, start, 100, Strategy::best(start, signal.len()))?;
+    Ok(to_number(&output[..8]))
 }
 
 #[cfg(test)]
@@ -95,8 +352,10 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse() {
-        assert_eq!(parse("1234"), vec![1, 2, 3, 4]);
+    fn test_parse() -> Result<()> {
+        assert_eq!(parse("1234\n")?, vec![1, 2, 3, 4]);
+        assert!(parse("12a4").is_err());
+        Ok(())
     }
     // #[test]
     // fn test_repeat_each() {
@@ -108,7 +367,7 @@ mod tests {
 
     #[test]
     fn test_round() -> Result<()> {
-        assert_eq!(round(parse("12345678")), vec![4, 8, 2, 2, 6, 1, 5, 8]);
+        assert_eq!(round(parse("12345678")?), vec![4, 8, 2, 2, 6, 1, 5, 8]);
         Ok(())
     }
 
@@ -120,6 +379,61 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_strategies() -> Result<()> {
+        let digits = parse("59791875142707344554745984624833270124746225787022156176")?;
+        for phases in 0..4 {
+            let naive = fft(&digits, 0, phases, Strategy::Naive)?;
+            assert_eq!(fft(&digits, 0, phases, Strategy::Prefix)?, naive);
+            for offset in [1, 7, 20, 28, 40, 56].iter().cloned() {
+                let expected = naive[offset..].to_vec();
+                assert_eq!(fft(&digits, offset, phases, Strategy::Naive)?, expected);
+                assert_eq!(fft(&digits, offset, phases, Strategy::Prefix)?, expected);
+                assert_eq!(
+                    fft(&digits, offset, phases, Strategy::Parallel(2))?,
+                    expected
+                );
+                if offset * 2 >= digits.len() {
+                    assert_eq!(fft(&digits, offset, phases, Strategy::Suffix)?, expected);
+                } else {
+                    assert!(fft(&digits, offset, phases, Strategy::Suffix).is_err());
+                }
+            }
+        }
+        assert!(fft(&digits, 57, 1, Strategy::Prefix).is_err());
+
+        // a chunk per thread and then some
+        let signal = repeated(&digits, 200);
+        let prefix = fft(&signal, 30, 3, Strategy::Prefix)?;
+        for threads in 1..4 {
+            assert_eq!(fft(&signal, 30, 3, Strategy::Parallel(threads))?, prefix);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_half_offset() -> Result<()> {
+        // the suffix trick doesn't work this early in the signal
+        let signal = repeated(&parse("80871224585914546619083218645595")?, 10);
+        let naive = fft(&signal, 100, 100, Strategy::Naive)?;
+        assert_eq!(fft(&signal, 100, 100, Strategy::Prefix)?, naive);
+        assert_eq!(Strategy::best(100, signal.len()), Strategy::Prefix);
+        assert_eq!("suffix".parse::<Strategy>()?, Strategy::Suffix);
+        assert!("fast".parse::<Strategy>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_modes() -> Result<()> {
+        let input = "80871224585914546619083218645595";
+        let options = Options::parse(vec!["repeat=2", "phases=1"])?;
+        assert_eq!(bench(input, &options)?.lines().count(), 5);
+        assert!(bench("123456", &options).is_err());
+        assert!(fft_mode("123456", &options).is_err());
+        assert!(part2("123456").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_part1() -> Result<()> {
         assert_eq!(part1("80871224585914546619083218645595")?, 24176176);
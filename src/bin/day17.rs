@@ -1,8 +1,6 @@
-use aoc2019::coor::Coor;
 use aoc2019::cpu::{set_memory, Cpu, CpuState};
+use aoc2019::grid::Grid;
 use aoc2019::{dispatch, Result};
-use failure::bail;
-use std::collections::HashMap;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -18,52 +16,36 @@ enum Tile {
     RobotRight,
 }
 
-fn read_map(cpu: &mut Cpu) -> Result<HashMap<Coor, Tile>> {
-    let mut map = HashMap::new();
-    let mut pos = Coor::new(0, 0);
-    let mut last_was_newline = false;
-    loop {
-        match cpu.run()? {
-            CpuState::Output(value) => {
-                if value == '\n' as i64 {
-                    if last_was_newline {
-                        break Ok(map);
-                    }
-                    last_was_newline = true;
-                    pos = Coor::new(0, pos.y + 1);
-                } else {
-                    last_was_newline = false;
-                    let tile = match value as u8 as char {
-                        '#' => Tile::Scaffold,
-                        '.' => Tile::Space,
-                        '^' => Tile::RobotUp,
-                        '>' => Tile::RobotRight,
-                        '<' => Tile::RobotLeft,
-                        'v' => Tile::RobotDown,
-                        c => bail!("invalid char: {}", c),
-                        // _ => break Ok(map),
-                    };
-                    map.insert(pos, tile);
-                    pos += Coor::new(1, 0);
-                }
-            }
-            _ => break Ok(map),
+fn read_map(cpu: &mut Cpu) -> Result<Grid<Tile>> {
+    let mut text = String::new();
+    while let CpuState::Output(value) = cpu.run()? {
+        text.push(value as u8 as char);
+        if text.ends_with("\n\n") {
+            break;
         }
     }
+    Grid::parse(&text, |c| match c {
+        '#' => Some(Tile::Scaffold),
+        '.' => Some(Tile::Space),
+        '^' => Some(Tile::RobotUp),
+        '>' => Some(Tile::RobotRight),
+        '<' => Some(Tile::RobotLeft),
+        'v' => Some(Tile::RobotDown),
+        _ => None,
+    })
 }
 
 fn part1(input: &str) -> Result<i64> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     let map = read_map(&mut cpu)?;
     let mut alignment = 0;
-    for coor in map.keys() {
-        if *map.get(coor).unwrap_or(&Tile::Space) == Tile::Scaffold
-            && *map.get(&(*coor + Coor::new(-1, 0))).unwrap_or(&Tile::Space) == Tile::Scaffold
-            && *map.get(&(*coor + Coor::new(1, 0))).unwrap_or(&Tile::Space) == Tile::Scaffold
-            && *map.get(&(*coor + Coor::new(0, 1))).unwrap_or(&Tile::Space) == Tile::Scaffold
-            && *map.get(&(*coor + Coor::new(0, -1))).unwrap_or(&Tile::Space) == Tile::Scaffold
+    for coor in map.find(|&t| t == Tile::Scaffold) {
+        if map
+            .neighbours4(coor)
+            .filter(|(_, &t)| t == Tile::Scaffold)
+            .count()
+            == 4
         {
-            // dbg!(coor);
             alignment += coor.x * coor.y;
         }
     }
@@ -77,7 +59,7 @@ fn part1(input: &str) -> Result<i64> {
 }
 
 fn part2(input: &str) -> Result<i64> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     set_memory(&mut cpu, 0, 2);
     //     .chars()
     //     .map(|c| c as u8 as i64)
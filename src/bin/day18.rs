@@ -1,8 +1,12 @@
+use aoc2019::bitset::BitSet;
 use aoc2019::coor::Coor;
+use aoc2019::graph::{compress, Cell, Graph};
+use aoc2019::grid::Grid;
+use aoc2019::memo::Memo;
+use aoc2019::search::{dijkstra, ByKey};
 use aoc2019::{dispatch, Result};
 use failure::err_msg;
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::ops::{Add, Sub};
+use std::collections::HashMap;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -16,181 +20,22 @@ enum Tile {
     Door(char),
 }
 
-fn parse(input: &str) -> (HashMap<Coor, Tile>, Coor) {
-    let mut map = HashMap::new();
-    let mut pos = Coor::new(0, 0);
-    let mut entrance = pos;
-    for c in input.chars() {
-        if c == '\n' {
-            pos = Coor::new(0, pos.y + 1);
-        } else {
-            let tile = match c {
-                '#' => Tile::Wall,
-                '.' => Tile::Open,
-                '@' => {
-                    entrance = pos;
-                    Tile::Open
-                }
-                c if 'a' <= c && c <= 'z' => Tile::Key(c),
-                c if 'A' <= c && c <= 'Z' => Tile::Door(c.to_ascii_lowercase()),
-                c => unreachable!("invalid char: {}", c),
-            };
-            map.insert(pos, tile);
-            pos += Coor::new(1, 0);
-        }
-    }
-    (map, entrance)
-}
-
-fn coor_key(coor: &Coor) -> usize {
-    (100 * coor.x + coor.y) as usize
-}
-
-#[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
-struct KeyBits {
-    n: u32,
-}
-
-impl KeyBits {
-    fn new(n: u32) -> Self {
-        KeyBits { n }
-    }
-
-    fn is_empty(&self) -> bool {
-        self.n == 0
-    }
-
-    fn contains(&self, key: char) -> bool {
-        // TODO: check
-        !self.n & KeyBits::char_to_bit(key) == 0
-    }
-
-    fn char_to_bit(key: char) -> u32 {
-        1 << (key as u8 - 'a' as u8)
-    }
-
-    fn _bit_to_char(bit: u32) -> char {
-        let mut n = 1;
-        let mut bit = bit;
-        while bit > 1 {
-            n += 1;
-            bit >>= 1;
-        }
-        (n - 1 + 'a' as u8) as char
-    }
-
-    fn display(&self) -> String {
-        (('a' as u8)..=('z' as u8))
-            .map(|c| (c, (1 << c - 'a' as u8)))
-            .filter(|(_, k)| self.n & k != 0)
-            .map(|(c, _)| c as char)
-            .collect::<String>()
-    }
-}
-
-impl std::convert::From<Option<char>> for KeyBits {
-    fn from(maybe_char: Option<char>) -> Self {
-        match maybe_char {
-            Some(c) => KeyBits::default() + c,
-            None => KeyBits::default(),
-        }
-    }
-}
-
-impl Add for KeyBits {
-    type Output = KeyBits;
-    fn add(self, other: KeyBits) -> KeyBits {
-        KeyBits::new(self.n | other.n)
-    }
-}
-
-impl Add<char> for KeyBits {
-    type Output = KeyBits;
-    fn add(self, other: char) -> KeyBits {
-        KeyBits::new(self.n | KeyBits::char_to_bit(other))
-    }
+fn parse(input: &str) -> Result<(Grid<Tile>, Coor)> {
+    let chars = Grid::parse(input, |c| match c {
+        '#' | '.' | '@' | 'a'..='z' | 'A'..='Z' => Some(c),
+        _ => None,
+    })?;
+    let entrance = chars.find(|&c| c == '@').next();
+    let map = chars.map(|_, &c| match c {
+        '#' => Tile::Wall,
+        c if c.is_ascii_lowercase() => Tile::Key(c),
+        c if c.is_ascii_uppercase() => Tile::Door(c.to_ascii_lowercase()),
+        _ => Tile::Open,
+    });
+    Ok((map, entrance.ok_or(err_msg("no entrance"))?))
 }
 
-impl Add<Option<char>> for KeyBits {
-    type Output = KeyBits;
-    fn add(self, other: Option<char>) -> KeyBits {
-        match other {
-            Some(c) => self + c,
-            None => self,
-        }
-    }
-}
-
-impl Sub for KeyBits {
-    type Output = KeyBits;
-    fn sub(self, other: KeyBits) -> KeyBits {
-        KeyBits::new(self.n & !other.n)
-    }
-}
-
-impl Sub<char> for KeyBits {
-    type Output = KeyBits;
-    fn sub(self, other: char) -> KeyBits {
-        KeyBits::new(self.n & !KeyBits::char_to_bit(other))
-    }
-}
-
-impl std::iter::FromIterator<char> for KeyBits {
-    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
-        KeyBits::new(
-            iter.into_iter()
-                .map(|c| KeyBits::char_to_bit(c))
-                .fold(0, |acc, x| acc | x),
-        )
-    }
-}
-
-impl std::fmt::Debug for KeyBits {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "KeyBits {{{}}}", self.display())
-    }
-}
-
-fn requirements(map: &[Tile], start: &Coor, end: &Coor) -> Option<(KeyBits, KeyBits, usize)> {
-    let mut seen = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((*start, KeyBits::default(), KeyBits::default(), 0));
-    while let Some((pos, required_keys, found_keys, distance)) = queue.pop_front() {
-        seen.insert(pos);
-        let neighbours = [
-            Coor::new(-1, 0),
-            Coor::new(1, 0),
-            Coor::new(0, -1),
-            Coor::new(0, 1),
-        ];
-
-        for mv in neighbours.iter() {
-            let new_pos = pos + *mv;
-            let mut required_key = None;
-            let mut found_key = None;
-            match map[coor_key(&new_pos)] {
-                Tile::Open => {}
-                Tile::Key(c) => found_key = Some(c),
-                Tile::Door(c) => required_key = Some(c),
-                Tile::Wall => continue,
-            }
-            if new_pos == *end {
-                return Some((required_keys, found_keys, distance + 1));
-            }
-
-            if seen.contains(&new_pos) {
-                continue;
-            }
-            queue.push_back((
-                new_pos,
-                required_keys + KeyBits::from(required_key),
-                found_keys + KeyBits::from(found_key),
-                distance + 1,
-            ));
-        }
-    }
-    None
-}
+type KeyBits = BitSet<u32>;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Reachable {
@@ -201,74 +46,69 @@ struct Reachable {
     distance: usize,
 }
 
-fn _all_requirements(
-    map: &HashMap<Coor, Tile>,
-    entrances: &[Coor],
-) -> HashMap<Coor, Vec<Reachable>> {
-    let keys_coors: Vec<(Coor, char)> = map
-        .iter()
-        .filter_map(|(c, v)| match v {
-            Tile::Key(k) => Some((*c, *k)),
-            _ => None,
-        })
-        .collect();
-    // let walkable_coors: Vec<Coor> = map
-    //     .iter()
-    //     .filter_map(|(c, v)| match v {
-    //         Tile::Wall => None,
-    //         _ => Some(*c),
-    //     })
-    //     .collect();
-    let mut starts = keys_coors.iter().map(|(c, _)| *c).collect::<Vec<_>>();
-    for entrance in entrances {
-        starts.push(*entrance);
-    }
-    let map_v = map_vec(&map);
-    let mut reqs = HashMap::new();
-    for &left in starts.iter() {
-        // for &left in walkable_coors.iter() {
-        let mut reachable = vec![];
-        'right: for &(right, key) in keys_coors.iter() {
-            if right == left {
-                continue;
-            }
-            for entrance in entrances {
-                if right == *entrance {
-                    continue 'right;
-                }
-            }
-            if let Some((required_keys, found_keys, distance)) = requirements(&map_v, &left, &right)
-            {
-                // if left == Coor::new(7, 3) && right == Coor::new(9, 1) {
-                // dbg!(&required_keys, &found_keys, distance);
-                // }
-                reachable.push(Reachable {
-                    pos: right,
+/// Keys reachable from `start`, with the doors passed and the keys picked up
+/// on the way.
+fn reachable(map: &Grid<Tile>, graph: &Graph<char>, start: Coor) -> Vec<Reachable> {
+    let outcome = dijkstra(
+        (start, KeyBits::default(), KeyBits::default()),
+        ByKey::new(|&(pos, _, _): &(Coor, KeyBits, KeyBits)| pos),
+        |&(pos, required_keys, found_keys)| {
+            let found_keys = match map.get(pos) {
+                Some(&Tile::Key(key)) if pos != start => found_keys.with_letter(key),
+                _ => found_keys,
+            };
+            graph
+                .edges(pos)
+                .iter()
+                .map(move |edge| {
+                    let required_keys = edge
+                        .crossed
+                        .iter()
+                        .fold(required_keys, |r, &d| r.with_letter(d));
+                    ((edge.to, required_keys, found_keys), edge.distance)
+                })
+                .collect::<Vec<_>>()
+        },
+        |_| false,
+    );
+    let mut reachable: Vec<Reachable> = outcome
+        .reached()
+        .filter_map(
+            |(&(pos, required_keys, found_keys), distance)| match map.get(pos) {
+                Some(&Tile::Key(key)) if pos != start => Some(Reachable {
+                    pos,
                     required_keys,
                     found_keys,
                     key,
                     distance,
-                });
-            }
-        }
-        reachable.sort_by_key(|r| r.distance);
-        // if left == Coor::new(7, 3) {
-        //     dbg!(&left, &reachable);
-        // }
-        reqs.insert(left, reachable);
-    }
-    reqs
+                }),
+                _ => None,
+            },
+        )
+        .collect();
+    reachable.sort_by_key(|r| r.distance);
+    reachable
 }
 
-fn map_vec(map: &HashMap<Coor, Tile>) -> Vec<Tile> {
-    let mut map_v = vec![Tile::Wall; 100 * 100];
-    for (coor, tile) in map {
-        map_v[coor_key(coor)] = *tile;
-    }
-    map_v
+/// Reachable keys from every key and entrance, found on the graph of
+/// corridors between them.
+fn _all_requirements(map: &Grid<Tile>, entrances: &[Coor]) -> HashMap<Coor, Vec<Reachable>> {
+    let graph = compress(
+        map,
+        |pos, tile| matches!(tile, Tile::Key(_)) || entrances.contains(&pos),
+        |tile| match *tile {
+            Tile::Wall => Cell::Blocked,
+            Tile::Door(key) => Cell::Annotated(key),
+            _ => Cell::Open,
+        },
+    );
+    graph
+        .nodes()
+        .map(|start| (start, reachable(map, &graph, start)))
+        .collect()
 }
 
-fn switch_entrance(map: HashMap<Coor, Tile>, entrance: Coor) -> (HashMap<Coor, Tile>, [Coor; 4]) {
+fn switch_entrance(map: Grid<Tile>, entrance: Coor) -> (Grid<Tile>, [Coor; 4]) {
     let mut map = map;
 
     map.insert(entrance + Coor::new(-1, -1), Tile::Open);
@@ -291,7 +131,7 @@ fn switch_entrance(map: HashMap<Coor, Tile>, entrance: Coor) -> (HashMap<Coor, T
     (map, entrances)
 }
 
-fn find_all_keys(map: &HashMap<Coor, Tile>) -> KeyBits {
+fn find_all_keys(map: &Grid<Tile>) -> KeyBits {
     map.values()
         .filter_map(|v| match v {
             Tile::Key(c) => Some(*c),
@@ -312,71 +152,53 @@ enum From {
     Key(char),
 }
 
-fn find_from(
-    map: &HashMap<Coor, Tile>,
-    reachable_map: &HashMap<Coor, Vec<Reachable>>,
+/// What the key searches need to know about the vault.
+struct Vault<'a> {
+    map: &'a Grid<Tile>,
+    reachable: HashMap<Coor, Vec<Reachable>>,
     all_keys: KeyBits,
+}
+
+impl Vault<'_> {
+    fn reachable_from(&self, from: From) -> Option<&[Reachable]> {
+        let coor = match from {
+            From::Entrance(coor) => coor,
+            From::Key(key) => self
+                .map
+                .find(|&v| v == Tile::Key(key))
+                .next()
+                .expect("key not found"),
+        };
+        self.reachable.get(&coor).map(Vec::as_slice)
+    }
+}
+
+fn find_from(
+    memo: &mut Memo<Vault, (From, KeyBits), Option<usize>>,
     from: From,
     keys: KeyBits,
-    mut cache: &mut HashMap<(From, KeyBits), usize>,
 ) -> Option<usize> {
-    // assume we're at `key`, and have everything but `keys`
-
-    // println!("find_from {}: {}", key, &keys.iter().collect::<String>());
-
+    // assume we're at `from`, and have everything but `keys`
     if keys.is_empty() {
         return Some(0);
     }
-    if let Some(&distance) = cache.get(&(from, keys)) {
-        return Some(distance);
-    }
-
-    let from_coor = match from {
-        From::Entrance(coor) => coor,
-        From::Key(key) => map
+    memo.get((from, keys), |memo, (from, keys)| {
+        let vault = memo.context();
+        let have = match from {
+            From::Key(k) => (vault.all_keys - keys).with_letter(k),
+            From::Entrance(_) => vault.all_keys - keys,
+        };
+        vault
+            .reachable_from(from)?
             .iter()
-            .filter_map(|(k, &v)| if v == Tile::Key(key) { Some(*k) } else { None })
-            .next()
-            .expect("key not found"),
-    };
-
-    let reachables = match reachable_map.get(&from_coor) {
-        None => {
-            return None;
-        }
-        Some(reachables) => reachables,
-    };
-
-    reachables
-        .iter()
-        .filter(|r| keys.contains(r.key))
-        .filter(|r| {
-            let have = all_keys - keys
-                + match from {
-                    From::Key(k) => Some(k),
-                    From::Entrance(_) => None,
-                };
-
-            (r.required_keys - have).is_empty()
-        })
-        .filter_map(|r| {
-            find_from(
-                &map,
-                &reachable_map,
-                all_keys,
-                From::Key(r.key),
-                keys - r.found_keys - r.key,
-                &mut cache,
-            )
-            .map(|d| d + r.distance)
-        })
-        // .inspect(|r| {})
-        .min()
-        .map(|distance| {
-            cache.insert((from, keys), distance);
-
-            distance
-        })
+            .filter(|r| keys.contains_letter(r.key))
+            .filter(|r| r.required_keys.is_subset(&have))
+            .filter_map(|r| {
+                let keys = (keys - r.found_keys).without_letter(r.key);
+                find_from(memo, From::Key(r.key), keys).map(|d| d + r.distance)
+            })
+            .min()
+    })
 }
 
 fn _print_from(from: [From; 4]) {
@@ -391,179 +213,72 @@ fn _print_from(from: [From; 4]) {
 }
 
 fn find_from4(
-    map: &HashMap<Coor, Tile>,
-    reachable_map: &HashMap<Coor, Vec<Reachable>>,
-    all_keys: KeyBits,
+    memo: &mut Memo<Vault, ([From; 4], KeyBits), Option<usize>>,
     from: [From; 4],
     keys: KeyBits,
-    // mut cache: &mut HashMap<(From, Vec<char>), usize>,
-    mut cache: &mut HashMap<([From; 4], KeyBits), Option<usize>>,
 ) -> Option<usize> {
-    // assume we're at `key`, and have everything but `keys`
-
-    // print!("find_from4 [");
-    // for f in &from {
-    //     match f {
-    //         From::Key(k) => print!("{}, ", k),
-    //         From::Entrance(c) => print!("{:?}, ", c),
-    //     }
-    // }
-    // println!("] to {:?}", &keys);
-
-    // if display_keys(&keys) == "cdfgjklmno".to_string() && from[0] == From::Key('b') {
-    //     dbg!(display_keys(&keys), &from);
-    // }
-
+    // assume the robots are at `from`, and have everything but `keys`
     if keys.is_empty() {
         return Some(0);
     }
-    if let Some(distance) = cache.get(&(from, keys)) {
-        return *distance;
-    }
-
-    let from_keys: KeyBits = from
-        .iter()
-        .filter_map(|f| match f {
-            From::Key(key) => Some(*key),
-            _ => None,
-        })
-        .collect();
-
-    let mut tmp: Vec<usize> = vec![];
-    for idx in 0..4 {
-        let indexed_from = from[idx];
-        let from_coor = match indexed_from {
-            From::Entrance(coor) => coor,
-            From::Key(key) => map
-                .iter()
-                .filter_map(|(k, &v)| if v == Tile::Key(key) { Some(*k) } else { None })
-                .next()
-                .expect("key not found"),
-        };
-
-        let reachables = match reachable_map.get(&from_coor) {
-            None => {
-                return None;
-            }
-            Some(reachables) => reachables,
-        };
-
-        if let Some(distance) = reachables
+    memo.get((from, keys), |memo, (from, keys)| {
+        let vault = memo.context();
+        let from_keys: KeyBits = from
             .iter()
-            .filter(|r| keys.contains(r.key))
-            .filter(|r| {
-                let have = all_keys - keys + from_keys;
-
-                (r.required_keys - have).is_empty()
-            })
-            .filter_map(|r| {
-                let mut prev_from = from.clone();
-                prev_from[idx] = From::Key(r.key);
-                let res = find_from4(
-                    &map,
-                    &reachable_map,
-                    all_keys,
-                    prev_from,
-                    keys - r.found_keys - from_keys - r.key,
-                    &mut cache,
-                );
-                // if indexed_from == From::Key('k') && key_v == vec!['j', 'l', 'm', 'n', 'o'] {
-                //     print_from(prev_from);
-                //     println!("");
-                //     println!("{:?}", from_coor);
-                //     println!("{:?}", r);
-                //     println!("{}", display_keys(&new_set));
-                //     dbg!(&res);
-                // }
-                res.map(|d| (r, d + r.distance))
-            })
-            .inspect(|(_r, _d)| {
-                // dbg!(&r, &keys, d);
-                {
-                    // println!("{:?}, {}, {}", &r, display_keys(&keys), d)
-                };
+            .filter_map(|f| match f {
+                From::Key(key) => Some(*key),
+                _ => None,
             })
-            .map(|(_, d)| d)
-            .min()
-            .map(|distance| {
-                // if let Some(found) = cache.get(&(indexed_from, key_v.clone())) {
-                //     if *found != distance
-                //         && key_v == vec!['j', 'l', 'm', 'n', 'o']
-                //         && indexed_from == From::Key('k')
-                //     {
-                //         dbg!(
-                //             &indexed_from,
-                //             &key_v.iter().collect::<String>(),
-                //             distance,
-                //             found
-                //         );
-                //     }
-                // } else {
-                //     // dbg!(&indexed_from, &key_v, distance, distance);
-                //     cache.insert((indexed_from, key_v.clone()), distance);
-                // }
-
-                distance
-            })
-        {
-            tmp.push(distance);
-        }
-    }
-    let res = tmp.into_iter().min();
+            .collect();
+        let have = (vault.all_keys - keys) | from_keys;
 
-    cache.insert((from, keys), res);
-
-    // print!("find_from4 [");
-    // for f in &from {
-    //     match f {
-    //         From::Key(k) => print!("{}, ", k),
-    //         From::Entrance(c) => print!("{:?}, ", c),
-    //     }
-    // }
-    // println!("] to {:?}: {:?}", &keys, res);
-
-    res
+        let mut distances = vec![];
+        for (idx, &robot) in from.iter().enumerate() {
+            let distance = vault
+                .reachable_from(robot)?
+                .iter()
+                .filter(|r| keys.contains_letter(r.key))
+                .filter(|r| r.required_keys.is_subset(&have))
+                .filter_map(|r| {
+                    let mut next = from;
+                    next[idx] = From::Key(r.key);
+                    let keys = (keys - r.found_keys - from_keys).without_letter(r.key);
+                    find_from4(memo, next, keys).map(|d| d + r.distance)
+                })
+                .min();
+            distances.extend(distance);
+        }
+        distances.into_iter().min()
+    })
 }
 
 fn part1(input: &str) -> Result<usize> {
-    let (map, entrance) = parse(input);
-    let reachable_map = _all_requirements(&map, &[entrance]);
-
-    let all_keys = find_all_keys(&map);
-    let mut cache = HashMap::new();
-    find_from(
-        &map,
-        &reachable_map,
-        all_keys,
-        From::Entrance(entrance),
-        all_keys,
-        &mut cache,
-    )
-    .ok_or(err_msg("empty pt1"))
+    let (map, entrance) = parse(input)?;
+    let vault = Vault {
+        map: &map,
+        reachable: _all_requirements(&map, &[entrance]),
+        all_keys: find_all_keys(&map),
+    };
+    let mut memo = Memo::new(&vault);
+    find_from(&mut memo, From::Entrance(entrance), vault.all_keys).ok_or(err_msg("empty pt1"))
 }
 
 fn part2(input: &str) -> Result<usize> {
-    let (map, entrance) = parse(input);
+    let (map, entrance) = parse(input)?;
     let (map, entrances) = switch_entrance(map, entrance);
-    let reachable_map = _all_requirements(&map, &entrances);
-
-    let all_keys = find_all_keys(&map);
-    let mut cache = HashMap::new();
-    let res = find_from4(
-        &map,
-        &reachable_map,
-        all_keys,
-        [
-            From::Entrance(entrances[0]),
-            From::Entrance(entrances[1]),
-            From::Entrance(entrances[2]),
-            From::Entrance(entrances[3]),
-        ],
-        all_keys,
-        &mut cache,
-    )
-    .ok_or(err_msg("empty (a)?"));
-    res
+    let vault = Vault {
+        map: &map,
+        reachable: _all_requirements(&map, &entrances),
+        all_keys: find_all_keys(&map),
+    };
+    let mut memo = Memo::new(&vault);
+    let from = [
+        From::Entrance(entrances[0]),
+        From::Entrance(entrances[1]),
+        From::Entrance(entrances[2]),
+        From::Entrance(entrances[3]),
+    ];
+    find_from4(&mut memo, from, vault.all_keys).ok_or(err_msg("empty (a)?"))
 }
 
 #[cfg(test)]
@@ -573,27 +288,27 @@ mod tests {
     #[test]
     fn test_key_bits() {
         let kb = KeyBits::default();
-        assert_eq!(kb.display(), "");
-        assert!(!kb.contains('a'));
+        assert_eq!(kb.letters(), "");
+        assert!(!kb.contains_letter('a'));
 
-        let kb1 = kb + 'a' + 'b';
-        assert_eq!(kb1.display(), "ab");
-        assert!(kb1.contains('a'));
+        let kb1 = kb.with_letter('a').with_letter('b');
+        assert_eq!(kb1.letters(), "ab");
+        assert!(kb1.contains_letter('a'));
 
-        let kb2 = kb1 - 'a';
-        assert_eq!(kb2.display(), "b");
-        assert!(kb2.contains('b'));
-        assert!(!kb2.contains('a'));
+        let kb2 = kb1.without_letter('a');
+        assert_eq!(kb2.letters(), "b");
+        assert!(kb2.contains_letter('b'));
+        assert!(!kb2.contains_letter('a'));
 
         assert_eq!((kb1 - kb), kb1);
         assert_eq!((kb2 - kb), kb2);
         assert_eq!((kb1 - kb2), vec!['a'].into_iter().collect());
         assert_eq!((kb2 - kb1), kb);
 
-        assert_eq!((kb2 + kb1), kb1);
+        assert_eq!((kb2 | kb1), kb1);
         assert_eq!(
             vec!['a', 'b'].into_iter().collect::<KeyBits>()
-                + vec!['a', 'c'].into_iter().collect::<KeyBits>(),
+                | vec!['a', 'c'].into_iter().collect::<KeyBits>(),
             vec!['a', 'b', 'c'].into_iter().collect::<KeyBits>()
         )
     }
@@ -1,7 +1,7 @@
 use aoc2019::coor::Coor;
 use aoc2019::cpu::{Cpu, CpuState};
+use aoc2019::grid::Grid;
 use aoc2019::{dispatch, Result};
-use std::collections::HashMap;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -9,20 +9,22 @@ fn main() -> Result<()> {
 
 struct Map {
     cpu: Cpu,
-    data: HashMap<Coor, bool>,
+    data: Grid<bool>,
 }
 
 impl Map {
-    fn new(input: &str) -> Self {
-        let mut cpu = Cpu::from_str(input);
-        cpu.run().unwrap();
-        let data: HashMap<Coor, bool> = HashMap::new();
-        Self { cpu, data }
+    fn new(input: &str) -> Result<Self> {
+        let mut cpu = Cpu::from_str(input)?;
+        cpu.run()?;
+        Ok(Self {
+            cpu,
+            data: Grid::sparse(),
+        })
     }
 
     fn get(&mut self, x: i64, y: i64) -> bool {
         let coor = Coor::new(x, y);
-        if let Some(&value) = self.data.get(&coor) {
+        if let Some(&value) = self.data.get(coor) {
             return value;
         }
         let mut cpu = self.cpu.clone();
@@ -43,7 +45,7 @@ impl Map {
 }
 
 fn part1(input: &str) -> Result<i64> {
-    let mut map = Map::new(input);
+    let mut map = Map::new(input)?;
     let mut sum = 0;
     for x in 0..50 {
         for y in 0..50 {
@@ -55,29 +57,23 @@ fn part1(input: &str) -> Result<i64> {
     Ok(sum)
 }
 
-fn _display(map: &Map, distance: i64) {
-    for y in 0..distance {
-        for x in 0..distance {
-            print!(
-                "{}",
-                match map.data.get(&Coor::new(x, y)) {
-                    None => " ",
-                    Some(true) => "#",
-                    Some(false) => ".",
-                }
-            )
-        }
-        print!("\n");
-    }
-    println!("\n");
+fn _display(map: &Map) {
+    println!(
+        "{}",
+        map.data.render(|tile| match tile {
+            None => ' ',
+            Some(true) => '#',
+            Some(false) => '.',
+        })
+    );
 }
 
 fn find_square(input: &str, size: i64) -> Result<i64> {
-    let mut map = Map::new(input);
+    let mut map = Map::new(input)?;
 
     let mut prev_x = 0;
     'y: for start_y in 0.. {
-        // display(&map, start_y + 1);
+        // display(&map);
 
         let mut start_x = prev_x;
         while !map.get(start_x, start_y) {
@@ -1,7 +1,11 @@
 use aoc2019::coor::Coor;
+use aoc2019::graph::{compress, Cell};
+use aoc2019::grid::Grid;
+use aoc2019::parse::grid;
+use aoc2019::search::dijkstra;
 use aoc2019::{dispatch, Result};
 use failure::err_msg;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
@@ -44,35 +48,23 @@ impl Label {
     }
 }
 
-fn parse(input: &str) -> (HashMap<Coor, Tile>, HashMap<Coor, Label>) {
-    let mut text_map = HashMap::new();
-    let mut pos = Coor::new(0, 0);
-    for c in input.chars() {
-        if c == '\n' {
-            pos = Coor::new(0, pos.y + 1);
-        } else {
-            if let Some(tile) = match c {
-                '#' => Some(Input::Wall),
-                '.' => Some(Input::Open),
-                ' ' => None,
-                c if 'A' <= c && c <= 'Z' => Some(Input::Label(c)),
-                c => unreachable!("invalid char: {}", c),
-            } {
-                text_map.insert(pos, tile);
-            }
-            pos += Coor::new(1, 0);
-        }
-    }
-
-    let mut coorsx = text_map.keys().collect::<Vec<_>>();
-    let mut coorsy = text_map.keys().collect::<Vec<_>>();
-    coorsx.sort_by_key(|c| -c.x);
-    coorsy.sort_by_key(|c| -c.y);
-    let max = Coor::new(coorsx[0].x, coorsy[0].y);
-
-    let mut map = HashMap::new();
+fn parse(input: &str) -> Result<(Grid<Tile>, HashMap<Coor, Label>)> {
+    let text_map: Grid<Input> = grid(input, |c| match c {
+        '#' => Some(Some(Input::Wall)),
+        '.' => Some(Some(Input::Open)),
+        ' ' => Some(None),
+        c if c.is_ascii_uppercase() => Some(Some(Input::Label(c))),
+        _ => None,
+    })?
+    .into_iter()
+    .filter_map(|(pos, tile)| tile.map(|t| (pos, t)))
+    .collect();
+
+    let max = text_map.bounds().ok_or(err_msg("empty map"))?.max;
+
+    let mut map = Grid::sparse();
     let mut labels = HashMap::new();
-    for (&coor, &tile) in &text_map {
+    for (coor, &tile) in text_map.iter() {
         match tile {
             Input::Label(c) => {
                 let up = coor + Coor::new(0, -1);
@@ -85,8 +77,8 @@ fn parse(input: &str) -> (HashMap<Coor, Tile>, HashMap<Coor, Label>) {
                     (up, down, 0),
                     (down, up, 1),
                 ] {
-                    if let Some(Input::Label(d)) = text_map.get(a) {
-                        if let Some(Input::Open) = text_map.get(b) {
+                    if let Some(Input::Label(d)) = text_map.get(*a) {
+                        if let Some(Input::Open) = text_map.get(*b) {
                             let label = if *order == 0 { [c, *d] } else { [*d, c] }
                                 .iter()
                                 .collect::<String>();
@@ -115,7 +107,7 @@ fn parse(input: &str) -> (HashMap<Coor, Tile>, HashMap<Coor, Label>) {
             }
         }
     }
-    (map, labels)
+    Ok((map, labels))
 }
 
 fn find_single_label(labels: &HashMap<Coor, Label>, label: &str) -> Result<Coor> {
@@ -136,202 +128,49 @@ fn find_other_label(labels: &HashMap<Coor, Label>, coor: &Coor) -> Option<(Coor,
         .next()
 }
 
-fn part1(input: &str) -> Result<i32> {
-    let (map, labels) = parse(input);
+/// Steps from AA to ZZ. In a recursive maze, portals change level and the
+/// outer ones are closed on the outermost level.
+fn shortest(input: &str, recursive: bool) -> Result<usize> {
+    let (map, labels) = parse(input)?;
     let start = find_single_label(&labels, "AA")?;
     let end = find_single_label(&labels, "ZZ")?;
 
-    let mut seen = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((start, 0, 0));
-
-    while let Some((pos, level, distance)) = queue.pop_front() {
-        if pos == end {
-            return Ok(distance);
-        }
-        seen.insert((pos, level));
-
-        for mv in [
-            Coor::new(-1, 0),
-            Coor::new(1, 0),
-            Coor::new(0, -1),
-            Coor::new(0, 1),
-        ]
-        .iter()
-        {
-            let next = pos + *mv;
-            match map.get(&next) {
-                None => continue,
-                Some(Tile::Wall) => continue,
-                Some(Tile::Open) => {}
-            }
-            if seen.contains(&(next, level)) {
-                continue;
-            }
-            queue.push_back((next, level, distance + 1));
-        }
-        if let Some((bridged, level_diff)) = find_other_label(&labels, &pos) {
-            let new_level = level + level_diff;
-            if !seen.contains(&(bridged, new_level)) {
-                queue.push_back((bridged, new_level, distance + 1));
+    // walk between portals only
+    let graph = compress(
+        &map,
+        |pos, _| labels.contains_key(&pos),
+        |&tile| match tile {
+            Tile::Wall => Cell::<()>::Blocked,
+            Tile::Open => Cell::Open,
+        },
+    );
+    dijkstra(
+        (start, 0),
+        HashSet::new(),
+        |&(pos, level)| {
+            let mut next: Vec<((Coor, i64), usize)> = graph
+                .neighbours(pos)
+                .map(|(n, distance)| ((n, level), distance))
+                .collect();
+            if let Some((bridged, level_diff)) = find_other_label(&labels, &pos) {
+                if !recursive {
+                    next.push(((bridged, level), 1));
+                } else if level + level_diff >= 0 {
+                    next.push(((bridged, level + level_diff), 1));
+                }
             }
-        }
-    }
-
-    Ok(0)
+            next
+        },
+        |&state| state == (end, 0),
+    )
+    .cost()
+    .ok_or_else(|| err_msg("no way out"))
 }
 
-fn part2(input: &str) -> Result<i32> {
-    let (map, labels) = parse(input);
-    let start = find_single_label(&labels, "AA")?;
-    let end = find_single_label(&labels, "ZZ")?;
-
-    let mut seen = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((start, 0, 0));
-
-    while let Some((pos, level, distance)) = queue.pop_front() {
-        if pos == end && level == 0 {
-            return Ok(distance);
-        }
-        seen.insert((pos, level));
-
-        for mv in [
-            Coor::new(-1, 0),
-            Coor::new(1, 0),
-            Coor::new(0, -1),
-            Coor::new(0, 1),
-        ]
-        .iter()
-        {
-            let next = pos + *mv;
-            match map.get(&next) {
-                None => continue,
-                Some(Tile::Wall) => continue,
-                Some(Tile::Open) => {}
-            }
-            if seen.contains(&(next, level)) {
-                continue;
-            }
-            queue.push_back((next, level, distance + 1));
-        }
-        if let Some((bridged, level_diff)) = find_other_label(&labels, &pos) {
-            let new_level = level + level_diff;
-            if new_level >= 0 && !seen.contains(&(bridged, new_level)) {
-                queue.push_back((bridged, new_level, distance + 1));
-            }
-        }
-    }
-
-    Ok(0)
+fn part1(input: &str) -> Result<usize> {
+    shortest(input, false)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_part1() -> Result<()> {
-        assert_eq!(
-            part1(
-                "         A
-         A
-  #######.#########
-  #######.........#
-  #######.#######.#
-  #######.#######.#
-  #######.#######.#
-  #####  B    ###.#
-BC...##  C    ###.#
-  ##.##       ###.#
-  ##...DE  F  ###.#
-  #####    G  ###.#
-  #########.#####.#
-DE..#######...###.#
-  #.#########.###.#
-FG..#########.....#
-  ###########.#####
-             Z
-             Z       "
-            )?,
-            23
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_part2() -> Result<()> {
-        assert_eq!(
-            part2(
-                "         A
-         A
-  #######.#########
-  #######.........#
-  #######.#######.#
-  #######.#######.#
-  #######.#######.#
-  #####  B    ###.#
-BC...##  C    ###.#
-  ##.##       ###.#
-  ##...DE  F  ###.#
-  #####    G  ###.#
-  #########.#####.#
-DE..#######...###.#
-  #.#########.###.#
-FG..#########.....#
-  ###########.#####
-             Z
-             Z       "
-            )?,
-            26
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_part2b() -> Result<()> {
-        assert_eq!(
-            part2(
-                "             Z L X W       C
-             Z P Q B       K
-  ###########.#.#.#.#######.###############
-  #...#.......#.#.......#.#.......#.#.#...#
-  ###.#.#.#.#.#.#.#.###.#.#.#######.#.#.###
-  #.#...#.#.#...#.#.#...#...#...#.#.......#
-  #.###.#######.###.###.#.###.###.#.#######
-  #...#.......#.#...#...#.............#...#
-  #.#########.#######.#.#######.#######.###
-  #...#.#    F       R I       Z    #.#.#.#
-  #.###.#    D       E C       H    #.#.#.#
-  #.#...#                           #...#.#
-  #.###.#                           #.###.#
-  #.#....OA                       WB..#.#..ZH
-  #.###.#                           #.#.#.#
-CJ......#                           #.....#
-  #######                           #######
-  #.#....CK                         #......IC
-  #.###.#                           #.###.#
-  #.....#                           #...#.#
-  ###.###                           #.#.#.#
-XF....#.#                         RF..#.#.#
-  #####.#                           #######
-  #......CJ                       NM..#...#
-  ###.#.#                           #.###.#
-RE....#.#                           #......RF
-  ###.###        X   X       L      #.#.#.#
-  #.....#        F   Q       P      #.#.#.#
-  ###.###########.###.#######.#########.###
-  #.....#...#.....#.......#...#.....#.#...#
-  #####.#.###.#######.#######.###.###.#.#.#
-  #.......#.......#.#.#.#.#...#...#...#.#.#
-  #####.###.#####.#.#.#.#.###.###.#.###.###
-  #.......#.....#.#...#...............#...#
-  #############.#.#.###.###################
-               A O F   N
-               A A D   M                     "
-            )?,
-            396
-        );
-        Ok(())
-    }
+fn part2(input: &str) -> Result<usize> {
+    shortest(input, true)
 }
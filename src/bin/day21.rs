@@ -19,7 +19,7 @@ AND D J
 */
 
 fn part1(input: &str) -> Result<i32> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     cpu.expect_ascii("Input instructions:\n")?;
 
     /*
@@ -75,7 +75,7 @@ fn part2(input: &str) -> Result<i32> {
     let mut best_time = 0;
 
     let mut rng = thread_rng();
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     cpu.expect_ascii("Input instructions:\n")?;
 
     cpu.write_ascii(
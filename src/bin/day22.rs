@@ -1,54 +1,11 @@
-use aoc2019::{dispatch, Result};
-use failure::{bail, Error};
-use std::ops;
+use aoc2019::modular::Affine;
+use aoc2019::parse::parse_lines;
+use aoc2019::{dispatch_modes, Options, Result};
+use failure::{bail, format_err, Error};
 use std::str::FromStr;
 
 type CardInt = i64;
 
-fn gcd_extended(a: CardInt, b: CardInt) -> (CardInt, CardInt, CardInt) {
-    // (b, x, y)
-
-    if a == 0 {
-        return (b, 0, 1);
-    }
-
-    let (g, x, y) = gcd_extended(b % a, a);
-
-    return (g, y - (b / a) * x, x);
-}
-
-fn mod_inverse(a: CardInt, m: CardInt) -> CardInt {
-    let (g, x, _) = gcd_extended(a, m);
-    if g != 1 {
-        unimplemented!("inverse doesn't exist");
-    }
-    // m is added to handle negative x
-    (x % m + m) % m
-}
-
-fn mod_div(a: CardInt, b: CardInt, m: CardInt) -> CardInt {
-    mod_mul(a, mod_inverse(b, m), m)
-}
-
-fn mod_mul(a: CardInt, b: CardInt, m: CardInt) -> CardInt {
-    let mut res = 0;
-    let mut a = a % m;
-    let mut b = b;
-    while b > 0 {
-        // If b is odd, add 'a' to result
-        if b % 2 == 1 {
-            res = (res + a) % m;
-        }
-
-        // Multiply 'a' with 2
-        a = (a * 2) % m;
-        // Divide b by 2
-        b /= 2;
-    }
-
-    res % m
-}
-
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 enum Shuffle {
     NewStack,
@@ -56,50 +13,6 @@ enum Shuffle {
     Deal(i64),
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Variable {
-    x: CardInt,
-    c: CardInt,
-    m: CardInt,
-}
-
-impl Variable {
-    fn new(x: CardInt, c: CardInt, m: CardInt) -> Self {
-        Self {
-            x: (x + m) % m,
-            c: (c + m) % m,
-            m,
-        }
-    }
-
-    fn eval(&self, card: CardInt) -> CardInt {
-        (mod_mul(self.x, card, self.m) + self.c + self.m) % self.m
-    }
-
-    fn invert(&self) -> Self {
-        // y = xc +b -> x = y/c - b/c
-        Variable::new(
-            mod_div(1, self.x, self.m),
-            mod_div(-self.c, self.x, self.m),
-            self.m,
-        )
-    }
-}
-
-impl ops::Mul for Variable {
-    type Output = Variable;
-    fn mul(self, other: Variable) -> Variable {
-        // (ax + c)(bx + d) = a(bx+d) +c = abx + ad + c
-        assert_eq!(self.m, other.m);
-        let m = self.m;
-        Variable::new(
-            mod_mul(self.x, other.x, m),
-            mod_mul(self.x, other.c, m) + self.c,
-            m,
-        )
-    }
-}
-
 impl Shuffle {
     fn _apply(&self, cards: Vec<CardInt>) -> Vec<CardInt> {
         let mut new = cards.clone();
@@ -123,35 +36,12 @@ impl Shuffle {
         new
     }
 
-    fn apply_to(&self, card: CardInt, len: CardInt) -> CardInt {
+    /// Where this moves the card at each position, in a deck of `len`.
+    fn affine(&self, len: CardInt) -> Affine {
         match *self {
-            Shuffle::NewStack => (len - 1 - card) % len,
-            Shuffle::Cut(amount) => (card - amount + len) % len,
-            Shuffle::Deal(amount) => mod_mul(card, amount, len),
-        }
-    }
-
-    fn apply_to_variable(&self, card: Variable, len: CardInt) -> Variable {
-        // v = x + c
-        // NewStack: -1 -v  -> -1 -(c+x) -> -x -1 -c ->
-        // Cut(amount): v - amount -> x + c -amount
-        // Deal(amount): amount * v -> amount *x + amount * c
-        match *self {
-            Shuffle::NewStack => Variable::new(-card.x, -1 - card.c, len),
-            Shuffle::Cut(amount) => Variable::new(card.x, card.c - amount, len),
-            Shuffle::Deal(amount) => Variable::new(
-                mod_mul(card.x, amount, len),
-                mod_mul(card.c, amount, len),
-                len,
-            ),
-        }
-    }
-
-    fn _apply_reverse_to(&self, card: CardInt, len: CardInt) -> CardInt {
-        match *self {
-            Shuffle::NewStack => (len - 1 - card) % len,
-            Shuffle::Cut(amount) => card + amount % len,
-            Shuffle::Deal(amount) => mod_div(card, amount, len),
+            Shuffle::NewStack => Affine::new(-1, -1, len),
+            Shuffle::Cut(amount) => Affine::new(1, -amount, len),
+            Shuffle::Deal(amount) => Affine::new(amount, 0, len),
         }
     }
 }
@@ -178,7 +68,7 @@ impl FromStr for Shuffle {
 }
 
 fn main() -> Result<()> {
-    dispatch(&part1, &part2)
+    dispatch_modes(&part1, &part2, &[("position", &position), ("card", &card)])
 }
 
 fn _run(shuffles: Vec<Shuffle>, size: CardInt) -> Vec<CardInt> {
@@ -189,72 +79,133 @@ fn _run(shuffles: Vec<Shuffle>, size: CardInt) -> Vec<CardInt> {
     cards
 }
 
-fn apply(shuffles: &[Shuffle], size: CardInt, card: CardInt) -> Result<CardInt> {
-    let mut card = card;
-    for shuffle in shuffles {
-        card = shuffle.apply_to(card, size);
-    }
-    Ok(card)
+fn combine(shuffles: &[Shuffle], len: CardInt) -> Affine {
+    shuffles
+        .iter()
+        .fold(Affine::identity(len), |total, s| total.then(&s.affine(len)))
+}
+
+/// Shuffles of a deck of `len` cards, as the single affine map from where a
+/// card starts to where it ends up.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct ShuffleProgram {
+    len: CardInt,
+    map: Affine,
 }
 
-fn _run2(shuffles: &Vec<Shuffle>, size: CardInt, card: CardInt, times: usize) -> CardInt {
-    let mut shuffles = shuffles.clone();
-    let mut card = card;
-    shuffles.reverse();
-    for _ in 0..times {
-        for shuffle in &shuffles {
-            card = shuffle._apply_reverse_to(card, size);
+impl ShuffleProgram {
+    fn new(shuffles: &[Shuffle], len: CardInt) -> Self {
+        ShuffleProgram {
+            len,
+            map: combine(shuffles, len),
         }
     }
-    card
+
+    /// The program run `times` times in a row.
+    fn repeat(&self, times: u64) -> Self {
+        ShuffleProgram {
+            len: self.len,
+            map: self.map.pow(times),
+        }
+    }
+
+    /// The program putting the cards back. Dealing with an increment that
+    /// shares a factor with the deck size loses cards, and can't be undone.
+    fn invert(&self) -> Result<Self> {
+        let map = self
+            .map
+            .invert()
+            .ok_or_else(|| format_err!("shuffle can't be undone in a deck of {}", self.len))?;
+        Ok(ShuffleProgram { len: self.len, map })
+    }
+
+    fn check(&self, index: CardInt) -> Result<()> {
+        if index < 0 || index >= self.len {
+            bail!("{} isn't in a deck of {}", index, self.len);
+        }
+        Ok(())
+    }
+
+    /// Where card `card` ends up, starting from a deck in order.
+    fn position_of(&self, card: CardInt) -> Result<CardInt> {
+        self.check(card)?;
+        Ok(self.map.apply(card))
+    }
+
+    /// The card that ends up at `position`, starting from a deck in order.
+    fn card_at(&self, position: CardInt) -> Result<CardInt> {
+        self.check(position)?;
+        Ok(self.invert()?.map.apply(position))
+    }
 }
 
 fn parse(input: &str) -> Result<Vec<Shuffle>> {
-    input
-        .split('\n')
-        .map(|l| l.parse())
-        .collect::<Result<Vec<Shuffle>>>()
+    parse_lines(input, str::parse)
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let shuffles = parse(input)?;
-    apply(&shuffles, 10007, 2019)
+/// The input shuffled `-o times=N` times in a deck of `-o deck=N` cards.
+fn program(input: &str, options: &Options) -> Result<ShuffleProgram> {
+    let deck = options.get("deck", DECK)?;
+    if deck < 1 {
+        bail!("a deck needs at least one card, got {}", deck);
+    }
+    let program = ShuffleProgram::new(&parse(input)?, deck);
+    Ok(program.repeat(options.get("times", 1)?))
 }
 
-fn part2(input: &str) -> Result<i64> {
-    let shuffles = parse(input)?;
-    let len = 119315717514047;
-    let mut card = Variable::new(1, 0, len);
-    for shuffle in &shuffles {
-        card = shuffle.apply_to_variable(card, len);
-    }
-    /* dbg!(&card); */
-    /* dbg!((mod_mul(2019, card.x, len) + card.c) % len); */
-    /* dbg!(mod_div(8326 - card.c, card.x, len)); */
-    /* dbg!(card.eval(2019)); */
-    /* dbg!(card.invert().eval(8326)); */
+/// Where `-o card=N` ends up.
+fn position(input: &str, options: &Options) -> Result<String> {
+    let card = options.get("card", CARD)?;
+    Ok(program(input, options)?.position_of(card)?.to_string())
+}
 
-    let mut pow = card;
+/// The card that ends up at `-o position=N`.
+fn card(input: &str, options: &Options) -> Result<String> {
+    let position = options.get("position", POSITION)?;
+    Ok(program(input, options)?.card_at(position)?.to_string())
+}
 
-    let times: i64 = 101741582076661;
-    let digits = format!("{:b}", times).chars().rev().collect::<Vec<_>>();
-    let mut total = Variable::new(1, 0, len);
-    for digit in digits.into_iter() {
-        if digit == '1' {
-            total = total * pow;
-        }
-        pow = pow * pow;
-    }
-    Ok(total.invert().eval(2020))
+const DECK: CardInt = 10007;
+const CARD: CardInt = 2019;
+const POSITION: CardInt = 2020;
+
+fn part1(input: &str) -> Result<i64> {
+    ShuffleProgram::new(&parse(input)?, DECK).position_of(CARD)
+}
+
+fn part2(input: &str) -> Result<i64> {
+    let len = 119_315_717_514_047;
+    let times = 101_741_582_076_661;
+    ShuffleProgram::new(&parse(input)?, len)
+        .repeat(times)
+        .card_at(POSITION)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2019::parse::ParseError;
 
     #[test]
-    fn test_mod_int() {
-        assert_eq!(mod_inverse(5, 8), 5);
+    fn test_combine() -> Result<()> {
+        let shuffles = parse(
+            "deal with increment 7
+deal into new stack
+deal into new stack",
+        )?;
+        let affine = combine(&shuffles, 10);
+        let cards = _run(shuffles, 10);
+        assert_eq!(cards, vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7]);
+        for (pos, &card) in cards.iter().enumerate() {
+            assert_eq!(affine.apply(card), pos as CardInt);
+        }
+
+        let shuffles = parse("cut -4\ndeal with increment 3\ndeal into new stack")?;
+        let affine = combine(&shuffles, 7);
+        for (pos, &card) in _run(shuffles, 7).iter().enumerate() {
+            assert_eq!(affine.apply(card), pos as CardInt);
+        }
+        Ok(())
     }
 
     #[test]
@@ -265,6 +216,58 @@ mod tests {
             "deal with increment 7".parse::<Shuffle>()?,
             Shuffle::Deal(7)
         );
+        let shuffles = parse("cut 3\r\ndeal into new stack\n")?;
+        assert_eq!(shuffles, vec![Shuffle::Cut(3), Shuffle::NewStack]);
+        let e = parse("cut 3\ncut x\n")
+            .expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError");
+        assert_eq!(e.line, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_program() -> Result<()> {
+        let programs = [
+            "deal with increment 7\ncut 3\ndeal into new stack",
+            "cut -4\ndeal with increment 3\ndeal into new stack",
+            "deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\n\
+             deal with increment 7\ncut 3\ndeal with increment 9\ndeal with increment 3\ncut -1",
+        ];
+        for &len in &[10, 11, 13] {
+            for input in programs.iter() {
+                let shuffles = parse(input)?;
+                let program = ShuffleProgram::new(&shuffles, len);
+                let mut cards: Vec<CardInt> = (0..len).collect();
+                for times in 0..5 {
+                    let repeated = program.repeat(times);
+                    for (position, &card) in cards.iter().enumerate() {
+                        let position = position as CardInt;
+                        assert_eq!(repeated.position_of(card)?, position);
+                        assert_eq!(repeated.card_at(position)?, card);
+                    }
+                    assert_eq!(repeated.invert()?.invert()?, repeated);
+                    for shuffle in shuffles.iter() {
+                        cards = shuffle._apply(cards);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_errors() -> Result<()> {
+        let program = ShuffleProgram::new(&parse("deal with increment 2")?, 10);
+        assert_eq!(program.position_of(3)?, 6);
+        assert!(program.card_at(6).is_err());
+        assert!(program.position_of(10).is_err());
+
+        for deck in &["deck=0", "deck=-7"] {
+            let options = Options::parse(vec![*deck])?;
+            assert!(position("cut 3", &options).is_err());
+            assert!(card("cut 3", &options).is_err());
+        }
         Ok(())
     }
 }
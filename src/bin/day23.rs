@@ -1,95 +1,120 @@
 use aoc2019::cpu::{Cpu, CpuState};
+use aoc2019::cycle::first_repeat;
 use aoc2019::{dispatch, Result};
-use std::collections::{HashSet, VecDeque};
+use failure::err_msg;
+use std::collections::VecDeque;
+use std::iter;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let cpu = Cpu::from_str(input);
-    let mut cpus: Vec<_> = (0..50).map(|_| cpu.clone()).collect();
-    let mut buffers: Vec<VecDeque<i64>> = (0..50).map(|_| VecDeque::new()).collect();
-    for i in 0..50 {
-        cpus[i].enqueue_input(i as i64);
+const SIZE: usize = 50;
+
+struct Network {
+    cpus: Vec<Cpu>,
+    buffers: Vec<VecDeque<i64>>,
+    inputs_since_last_output: [usize; SIZE],
+    nat: Option<(i64, i64)>,
+}
+
+impl Network {
+    fn new(input: &str) -> Result<Self> {
+        let cpu = Cpu::from_str(input)?;
+        let mut cpus: Vec<_> = (0..SIZE).map(|_| cpu.clone()).collect();
+        for (address, cpu) in cpus.iter_mut().enumerate() {
+            cpu.enqueue_input(address as i64);
+        }
+        Ok(Network {
+            cpus,
+            buffers: (0..SIZE).map(|_| VecDeque::new()).collect(),
+            inputs_since_last_output: [0; SIZE],
+            nat: None,
+        })
     }
-    'main: loop {
-        // for (index, cpu) in cpus.iter().enumerate() {
-        for index in 0..50 {
-            let cpu = &mut cpus[index];
+
+    fn is_idle(&self) -> bool {
+        self.inputs_since_last_output.iter().all(|&c| c > 2)
+    }
+
+    /// Runs each computer until it blocks once, returning the first packet
+    /// sent to the NAT, if any.
+    fn tick(&mut self) -> Result<Option<(i64, i64)>> {
+        let mut sent_to_nat = None;
+        for index in 0..SIZE {
+            let cpu = &mut self.cpus[index];
             match cpu.run()? {
                 CpuState::NeedsInput => {
+                    self.inputs_since_last_output[index] += 1;
                     cpu.enqueue_input(-1);
                 }
                 CpuState::Output(value) => {
-                    buffers[index].push_back(value);
-                    if buffers[index].len() == 3 {
-                        let destination = buffers[index].pop_front().unwrap();
-                        let x = buffers[index].pop_front().unwrap();
-                        let y = buffers[index].pop_front().unwrap();
+                    self.inputs_since_last_output[index] = 0;
+                    let buffer = &mut self.buffers[index];
+                    buffer.push_back(value);
+                    if buffer.len() == 3 {
+                        let destination = buffer.pop_front().expect("destination");
+                        let x = buffer.pop_front().expect("x");
+                        let y = buffer.pop_front().expect("y");
                         if destination == 255 {
-                            break 'main (Ok(y));
+                            self.nat = Some((x, y));
+                            if sent_to_nat.is_none() {
+                                sent_to_nat = self.nat;
+                            }
+                        } else {
+                            self.cpus[destination as usize].enqueue_input(x);
+                            self.cpus[destination as usize].enqueue_input(y);
                         }
-                        cpus[destination as usize].enqueue_input(x);
-                        cpus[destination as usize].enqueue_input(y);
                     }
                 }
                 CpuState::Halted => {}
             }
         }
+        Ok(sent_to_nat)
     }
-}
 
-fn part2(input: &str) -> Result<i64> {
-    let cpu = Cpu::from_str(input);
-    let mut cpus: Vec<_> = (0..50).map(|_| cpu.clone()).collect();
-    let mut buffers: Vec<VecDeque<i64>> = (0..50).map(|_| VecDeque::new()).collect();
-    let mut inputs_since_last_output = [0; 50];
-    for i in 0..50 {
-        cpus[i].enqueue_input(i as i64);
+    /// Runs until the network is idle, then has the NAT send its last packet
+    /// to computer 0.
+    fn wake(&mut self) -> Result<(i64, i64)> {
+        while !self.is_idle() {
+            self.tick()?;
+        }
+        let (x, y) = self
+            .nat
+            .ok_or_else(|| err_msg("idle before any packet reached the NAT"))?;
+        self.cpus[0].enqueue_input(x);
+        self.cpus[0].enqueue_input(y);
+        self.inputs_since_last_output[0] = 0;
+        Ok((x, y))
     }
-    let mut nat = None;
-    let mut seen_ys = HashSet::new();
+}
 
-    // let mut round = 0;
-    'main: loop {
-        // round += 1;
-        if inputs_since_last_output.iter().all(|&c| c > 2) {
-            // dbg!("idle", round);
-            let (x, y) = nat.expect("read nat");
-            if seen_ys.contains(&y) {
-                break Ok(y);
-            }
-            seen_ys.insert(y);
-            cpus[0].enqueue_input(x);
-            cpus[0].enqueue_input(y);
+fn part1(input: &str) -> Result<i64> {
+    let mut network = Network::new(input)?;
+    loop {
+        if let Some((_, y)) = network.tick()? {
+            return Ok(y);
         }
-        for index in 0..50 {
-            let cpu = &mut cpus[index];
-            match cpu.run()? {
-                CpuState::NeedsInput => {
-                    inputs_since_last_output[index] += 1;
-                    cpu.enqueue_input(-1);
-                }
-                CpuState::Output(value) => {
-                    inputs_since_last_output[index] = 0;
-                    buffers[index].push_back(value);
-                    if buffers[index].len() == 3 {
-                        let destination = buffers[index].pop_front().unwrap();
-                        let x = buffers[index].pop_front().unwrap();
-                        let y = buffers[index].pop_front().unwrap();
-                        if destination == 255 {
-                            nat = Some((x, y));
-                        } else {
-                            cpus[destination as usize].enqueue_input(x);
-                            cpus[destination as usize].enqueue_input(y);
-                        }
-                    }
-                }
-                CpuState::Halted => {}
-            }
+    }
+}
+
+fn part2(input: &str) -> Result<i64> {
+    let mut network = Network::new(input)?;
+    let mut error = None;
+    let sent_ys = iter::from_fn(|| match network.wake() {
+        Ok((_, y)) => Some(y),
+        Err(e) => {
+            error = Some(e);
+            None
         }
+    });
+    let repeated = first_repeat(sent_ys);
+    if let Some(e) = error {
+        return Err(e);
     }
+    repeated
+        .map(|(_, y)| y)
+        .ok_or_else(|| err_msg("network stopped"))
 }
 
 #[cfg(test)]
@@ -101,4 +126,17 @@ mod tests {
         assert_eq!(part1("")?, 0);
         Ok(())
     }
+
+    #[test]
+    fn test_first_nat_packet() -> Result<()> {
+        // every computer sends (address, address + 100) to the NAT
+        let program = "3,13,104,255,4,13,1001,13,100,14,4,14,99,0,0";
+        let mut network = Network::new(program)?;
+        assert_eq!(network.tick()?, None);
+        assert_eq!(network.tick()?, None);
+        assert_eq!(network.tick()?, Some((0, 100)));
+        assert_eq!(network.nat, Some((49, 149)));
+        assert_eq!(part1(program)?, 100);
+        Ok(())
+    }
 }
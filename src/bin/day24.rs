@@ -1,268 +1,108 @@
+use aoc2019::automaton::{Automaton, Bounded, Neighbourhood, Recursive, Rule};
+use aoc2019::bitset::BitSet;
+use aoc2019::coor::{Bounds, Coor};
+use aoc2019::grid::Grid;
 use aoc2019::{dispatch, Result};
-use std::collections::HashSet;
 
 fn main() -> Result<()> {
     dispatch(&part1, &part2)
 }
 
-const SIZE: i8 = 5;
+const SIZE: i64 = 5;
 
-fn idx2coor(idx: usize) -> (i8, i8) {
-    let idx = idx as i8;
-    (idx % SIZE, idx / SIZE)
-}
-
-fn coor2idx(coor: (i8, i8)) -> usize {
-    (coor.1 * SIZE + coor.0) as usize
-}
-
-fn count(cells: &[bool], coor: (i8, i8)) -> usize {
-    [(-1, 0), (1, 0), (0, -1), (0, 1)]
-        .iter()
-        .map(|d| (d.0 + coor.0, d.1 + coor.1))
-        .filter(|c| c.0 >= 0 && c.0 < SIZE && c.1 >= 0 && c.1 < SIZE)
-        .filter(|&c| cells[coor2idx(c)])
-        .count()
-}
+/// Bugs on a 5x5 layout, bit `y * 5 + x` set for a bug at `(x, y)`.
+type Layout = BitSet<u32>;
 
-fn next(cells: &[bool]) -> Vec<bool> {
-    cells
-        .iter()
-        .enumerate()
-        .map(|(idx, &c)| match (c, count(cells, idx2coor(idx))) {
-            (true, 1) => true,
-            (true, _) => false,
-            (false, 1) => true,
-            (false, 2) => true,
-            (b, _) => b,
-        })
-        .collect()
+fn bounds() -> Bounds {
+    Bounds::new(Coor::default(), Coor::new(SIZE - 1, SIZE - 1))
 }
 
-fn _draw(cells: &[bool]) {
-    for y in 0..SIZE {
-        for x in 0..SIZE {
-            print!(
-                "{}",
-                if x == 2 && y == 2 {
-                    '?'
-                } else {
-                    match cells[coor2idx((x, y))] {
-                        false => '.',
-                        true => '#',
-                    }
-                }
-            );
-        }
-        print!("\n");
-    }
-    print!("\n");
+/// A bug dies unless it has exactly one neighbour, an empty tile gets
+/// infested with one or two.
+fn rule() -> Rule {
+    Rule::new(&[1, 2], &[1])
 }
 
-fn rating(cells: &[bool]) -> u32 {
-    let mut sum = 0;
-    for (idx, &val) in cells.iter().enumerate() {
-        if val {
-            sum += 1 << idx;
-        }
-    }
-    sum
-}
-
-fn parse(input: &str) -> Vec<bool> {
-    input
-        .chars()
-        .filter_map(|c| match c {
-            '.' | '?' => Some(false),
-            '#' => Some(true),
-            '\n' => None,
-            c => panic!("Unexpected char: {}", c),
-        })
-        .collect()
-}
-
-fn part1(input: &str) -> Result<u32> {
-    let mut cells = parse(input);
-    /* draw(&cells); */
-    let mut seen = HashSet::new();
-    loop {
-        let n = rating(&cells);
-        if seen.contains(&n) {
-            return Ok(n);
-        }
-        seen.insert(n);
-        cells = next(&cells);
-    }
+fn flat(bugs: Vec<Coor>) -> Automaton<Bounded> {
+    let topology = Bounded {
+        bounds: bounds(),
+        neighbourhood: Neighbourhood::VonNeumann,
+    };
+    Automaton::new(topology, rule(), bugs)
 }
 
-type Coor3 = (i8, i8, i64);
-// type Vec3 = Vec<Vec<Vec<bool>>>;
-
-struct Cells {
-    data: Vec<Vec<bool>>,
+fn recursive(bugs: Vec<Coor>) -> Automaton<Recursive> {
+    let topology = Recursive { size: SIZE };
+    Automaton::new(topology, rule(), bugs.into_iter().map(|c| (c, 0)))
 }
-impl Cells {
-    // fn new(levels: usize) -> Self {
-    //     let mut data = vec![];
-    //     for _ in 0..levels {
-    //         data.push(vec![false; 25])
-    //     }
-    //     Self { data }
-    // }
 
-    fn get(&self, x: i8, y: i8, level: i64) -> bool {
-        if level < 0 || level >= self.data.len() as i64 {
-            return false;
-        }
-        if x < 0 || x >= SIZE || y < 0 || y >= SIZE {
-            panic!("Bad (x,y): ({},{})", x, y);
-        }
-        self.data[level as usize][coor2idx((x, y))]
-    }
-
-    fn count(&self) -> usize {
-        self.data
-            .iter()
-            .map(|level| level.iter().filter(|b| **b).count())
-            .sum()
-    }
-
-    fn _draw(&self) {
-        for level in &self.data {
-            // if level.iter().any(|b| *b) {
-            _draw(&level);
-            // }
-        }
+fn _draw<'a, I: IntoIterator<Item = &'a Coor>>(bugs: I) {
+    let mut grid = Grid::dense(bounds(), '.');
+    grid.insert(Coor::new(2, 2), '?');
+    for &bug in bugs {
+        grid.insert(bug, '#');
     }
+    println!("{}", grid.render(|c| *c.unwrap_or(&' ')));
 }
 
-fn count_neighbours_at(cells: &Cells, coor: Coor3, from: Coor3) -> usize {
-    let single = |b| if b { 1 } else { 0 };
-    if coor.0 < 0 {
-        return single(cells.get(1, 2, coor.2 - 1));
-    }
-    if coor.0 >= SIZE {
-        return single(cells.get(3, 2, coor.2 - 1));
-    }
-
-    if coor.1 < 0 {
-        return single(cells.get(2, 1, coor.2 - 1));
-    }
-    if coor.1 >= SIZE {
-        return single(cells.get(2, 3, coor.2 - 1));
-    }
-
-    if coor.0 == 2 && coor.1 == 2 {
-        return match (from.0, from.1) {
-            (1, 2) => (0..5).map(|i| single(cells.get(0, i, coor.2 + 1))).sum(),
-            (2, 1) => (0..5).map(|i| single(cells.get(i, 0, coor.2 + 1))).sum(),
-            (3, 2) => (0..5).map(|i| single(cells.get(4, i, coor.2 + 1))).sum(),
-            (2, 3) => (0..5).map(|i| single(cells.get(i, 4, coor.2 + 1))).sum(),
-            _ => {
-                unreachable!("Invalid neighbour: {:?}, {:?}", coor, from);
-            }
-        };
-    }
-
-    return single(cells.get(coor.0, coor.1, coor.2));
+fn rating<'a, I: IntoIterator<Item = &'a Coor>>(bugs: I) -> u32 {
+    bugs.into_iter()
+        .map(|c| (c.y * SIZE + c.x) as usize)
+        .collect::<Layout>()
+        .bits()
 }
 
-fn counti(cells: &Cells, coor: Coor3) -> usize {
-    [(-1, 0), (1, 0), (0, -1), (0, 1)]
-        .iter()
-        .map(|d| (d.0 + coor.0, d.1 + coor.1))
-        .map(|c| count_neighbours_at(&cells, (c.0, c.1, coor.2), coor))
-        .sum()
+fn parse(input: &str) -> Result<Vec<Coor>> {
+    let cells = Grid::parse(input, |c| match c {
+        '.' | '?' => Some(false),
+        '#' => Some(true),
+        _ => None,
+    })?;
+    Ok(cells.find(|&bug| bug).collect())
 }
 
-fn nexti(cells: Cells) -> Cells {
-    let mut extended_data = cells.data.clone();
-    extended_data.insert(0, vec![false; 25]);
-    extended_data.push(vec![false; 25]);
-    let extended_cells = Cells {
-        data: extended_data.clone(),
-    };
-    Cells {
-        data: extended_data
-            .iter()
-            .enumerate()
-            .map(|(level_idx, level)| {
-                level
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, &c)| {
-                        let coor2 = idx2coor(idx);
-                        if coor2 == (2, 2) {
-                            return false;
-                        }
-                        let count = counti(&extended_cells, (coor2.0, coor2.1, level_idx as i64));
-                        let new = match (c, count) {
-                            (true, 1) => true,
-                            (true, _) => false,
-                            (false, 1) => true,
-                            (false, 2) => true,
-                            (b, _) => b,
-                        };
-                        new
-                    })
-                    .collect()
-            })
-            .collect(),
-    }
+fn part1(input: &str) -> Result<u32> {
+    let (_, repeated) = flat(parse(input)?).find_cycle();
+    Ok(rating(&repeated))
 }
 
 fn part2(input: &str) -> Result<usize> {
-    let mut cells = Cells {
-        data: vec![parse(input)],
-    };
-    for _ in 0..200 {
-        cells = nexti(cells);
-    }
-    Ok(cells.count())
+    let mut bugs = recursive(parse(input)?);
+    bugs.run(200);
+    Ok(bugs.population())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_conversion() {
-        assert_eq!(coor2idx((0, 0)), 0);
-        assert_eq!(coor2idx((2, 2)), 12);
-        assert_eq!(coor2idx((4, 4)), 24);
-        assert_eq!(coor2idx(idx2coor(0)), 0);
-        assert_eq!(coor2idx(idx2coor(24)), 24);
-        assert_eq!(idx2coor(coor2idx((0, 0))), (0, 0));
-        assert_eq!(idx2coor(coor2idx((1, 2))), (1, 2));
-        assert_eq!(idx2coor(coor2idx((4, 4))), (4, 4));
-    }
+    const SAMPLE: &str = "\
+....#
+#..#.
+#..##
+..#..
+#....";
 
     #[test]
-    fn test_count() {
-        let cells = parse(
+    fn test_count() -> Result<()> {
+        let bugs = flat(parse(
             "\
 #....
 .....
 ...#.
 ..#.#
 ...#.",
-        );
-        assert_eq!(count(&cells, (0, 0)), 0);
-        assert_eq!(count(&cells, (0, 1)), 1);
-        assert_eq!(count(&cells, (1, 0)), 1);
-        assert_eq!(count(&cells, (3, 3)), 4);
+        )?);
+        assert_eq!(bugs.live_neighbours(&Coor::new(0, 0)), 0);
+        assert_eq!(bugs.live_neighbours(&Coor::new(0, 1)), 1);
+        assert_eq!(bugs.live_neighbours(&Coor::new(1, 0)), 1);
+        assert_eq!(bugs.live_neighbours(&Coor::new(3, 3)), 4);
+        Ok(())
     }
 
     #[test]
-    fn test_next() {
-        let cells = parse(
-            "\
-....#
-#..#.
-#..##
-..#..
-#....",
-        );
+    fn test_next() -> Result<()> {
+        let mut bugs = flat(parse(SAMPLE)?);
         let expected = parse(
             "\
 #..#.
@@ -270,66 +110,50 @@ mod tests {
 ###.#
 ##.##
 .##..",
-        );
-        assert_eq!(count(&cells, (0, 4)), 0);
-        _draw(&next(&cells));
-        assert_eq!(next(&cells), expected);
+        )?;
+        assert_eq!(bugs.live_neighbours(&Coor::new(0, 4)), 0);
+        bugs.step();
+        _draw(bugs.live());
+        assert_eq!(bugs.live().iter().cloned().collect::<Vec<_>>(), expected);
+        Ok(())
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(
-            part1(
-                "\
-....#
-#..#.
-#..##
-..#..
-#...."
-            )?,
-            2129920
-        );
+        assert_eq!(part1(SAMPLE)?, 2129920);
         Ok(())
     }
 
     #[test]
     fn test_count_neighbours_at_19() {
-        let mut middle = vec![false; (SIZE * SIZE) as usize];
-        middle[coor2idx((3, 2))] = true;
-        middle[coor2idx((2, 3))] = true;
-        middle[coor2idx((3, 4))] = true;
-        middle[coor2idx((4, 3))] = true;
-        let cells = Cells {
-            data: vec![
-                vec![false; (SIZE * SIZE) as usize],
-                middle,
-                vec![false; (SIZE * SIZE) as usize],
-            ],
-        };
-        assert_eq!(cells.count(), 4);
-        assert_eq!(counti(&cells, (3, 3, 1)), 4);
+        let middle = vec![(3, 2), (2, 3), (3, 4), (4, 3)];
+        let bugs = Automaton::new(
+            Recursive { size: SIZE },
+            rule(),
+            middle.into_iter().map(|(x, y)| (Coor::new(x, y), 1)),
+        );
+        assert_eq!(bugs.population(), 4);
+        assert_eq!(bugs.live_neighbours(&(Coor::new(3, 3), 1)), 4);
     }
 
     #[test]
     fn test_count_neighbours_at_14() {
-        let mut middle = vec![false; (SIZE * SIZE) as usize];
-        middle[coor2idx((3, 1))] = true;
-        middle[coor2idx((3, 3))] = true;
-        middle[coor2idx((4, 2))] = true;
-        let mut bottom = vec![false; (SIZE * SIZE) as usize];
-        for i in 0..5 {
-            bottom[coor2idx((4, i))] = true;
-        }
-        let cells = Cells {
-            data: vec![vec![false; (SIZE * SIZE) as usize], middle, bottom],
-        };
-        assert_eq!(cells.count(), 8);
-        assert_eq!(counti(&cells, (3, 2, 1)), 8);
+        let middle = vec![(3, 1), (3, 3), (4, 2)].into_iter().map(|c| (c, 1));
+        let inner = (0..5).map(|i| ((4, i), 2));
+        let bugs = Automaton::new(
+            Recursive { size: SIZE },
+            rule(),
+            middle
+                .chain(inner)
+                .map(|((x, y), depth)| (Coor::new(x, y), depth)),
+        );
+        assert_eq!(bugs.population(), 8);
+        assert_eq!(bugs.live_neighbours(&(Coor::new(3, 2), 1)), 8);
     }
 
     #[test]
-    fn test_counti() {
-        let initial = parse(
+    fn test_recursive() -> Result<()> {
+        let mut bugs = recursive(parse(
             "\
 ....#
 #..#.
@@ -337,28 +161,10 @@ mod tests {
 ..#..
 #....
 ",
-        );
-        let mut cells = Cells {
-            data: vec![initial],
-        };
-
-        let mut extended_data = cells.data.clone();
-        extended_data.insert(0, vec![false; 25]);
-        extended_data.push(vec![false; 25]);
-
-        assert_eq!(
-            counti(
-                &Cells {
-                    data: extended_data
-                },
-                (0, 0, 2)
-            ),
-            0
-        );
-        for _ in 0..10 {
-            cells = nexti(cells);
-        }
-        // cells._draw();
-        assert_eq!(cells.count(), 99);
+        )?);
+        assert_eq!(bugs.live_neighbours(&(Coor::new(0, 0), 1)), 0);
+        bugs.run(10);
+        assert_eq!(bugs.population(), 99);
+        Ok(())
     }
 }
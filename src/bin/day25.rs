@@ -9,7 +9,7 @@ fn main() -> Result<()> {
 }
 
 fn part1(input: &str) -> Result<i32> {
-    let mut cpu = Cpu::from_str(input);
+    let mut cpu = Cpu::from_str(input)?;
     cpu.write_ascii(
         "\
 north
@@ -0,0 +1,302 @@
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, Sub};
+
+/// Storage for a `BitSet`: a fixed number of bits.
+pub trait Bits: Copy + Eq + Hash {
+    const CAPACITY: usize;
+
+    fn empty() -> Self;
+    fn get(&self, idx: usize) -> bool;
+    fn set(&mut self, idx: usize, value: bool);
+    fn or(self, other: Self) -> Self;
+    fn and(self, other: Self) -> Self;
+    fn and_not(self, other: Self) -> Self;
+    fn count(&self) -> usize;
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {
+        $(
+            impl Bits for $t {
+                const CAPACITY: usize = <$t>::BITS as usize;
+
+                fn empty() -> Self {
+                    0
+                }
+
+                fn get(&self, idx: usize) -> bool {
+                    self >> idx & 1 == 1
+                }
+
+                fn set(&mut self, idx: usize, value: bool) {
+                    if value {
+                        *self |= 1 << idx;
+                    } else {
+                        *self &= !(1 << idx);
+                    }
+                }
+
+                fn or(self, other: Self) -> Self {
+                    self | other
+                }
+
+                fn and(self, other: Self) -> Self {
+                    self & other
+                }
+
+                fn and_not(self, other: Self) -> Self {
+                    self & !other
+                }
+
+                fn count(&self) -> usize {
+                    self.count_ones() as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_bits!(u32, u64, u128);
+
+impl<const N: usize> Bits for [u64; N] {
+    const CAPACITY: usize = 64 * N;
+
+    fn empty() -> Self {
+        [0; N]
+    }
+
+    fn get(&self, idx: usize) -> bool {
+        self[idx / 64].get(idx % 64)
+    }
+
+    fn set(&mut self, idx: usize, value: bool) {
+        self[idx / 64].set(idx % 64, value)
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        for (a, b) in self.iter_mut().zip(other.iter()) {
+            *a |= b;
+        }
+        self
+    }
+
+    fn and(mut self, other: Self) -> Self {
+        for (a, b) in self.iter_mut().zip(other.iter()) {
+            *a &= b;
+        }
+        self
+    }
+
+    fn and_not(mut self, other: Self) -> Self {
+        for (a, b) in self.iter_mut().zip(other.iter()) {
+            *a &= !b;
+        }
+        self
+    }
+
+    fn count(&self) -> usize {
+        self.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// Index of a letter, `a` and `A` both being 0.
+pub fn letter_index(letter: char) -> usize {
+    assert!(letter.is_ascii_alphabetic(), "not a letter: {:?}", letter);
+    (letter.to_ascii_lowercase() as u8 - b'a') as usize
+}
+
+/// The lowercase letter at `idx`.
+pub fn index_letter(idx: usize) -> char {
+    assert!(idx < 26, "no letter at {}", idx);
+    (b'a' + idx as u8) as char
+}
+
+/// A set of small indices, stored as the bits of `B`. Indices must be below
+/// `B::CAPACITY`.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct BitSet<B = u64> {
+    bits: B,
+}
+
+impl<B: Bits> BitSet<B> {
+    pub fn new() -> Self {
+        BitSet { bits: B::empty() }
+    }
+
+    pub fn from_bits(bits: B) -> Self {
+        BitSet { bits }
+    }
+
+    pub fn bits(&self) -> B {
+        self.bits
+    }
+
+    pub fn capacity(&self) -> usize {
+        B::CAPACITY
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == B::empty()
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        idx < B::CAPACITY && self.bits.get(idx)
+    }
+
+    pub fn insert(&mut self, idx: usize) {
+        assert!(idx < B::CAPACITY, "{} out of range", idx);
+        self.bits.set(idx, true);
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < B::CAPACITY {
+            self.bits.set(idx, false);
+        }
+    }
+
+    /// A copy with `idx` added.
+    pub fn with(mut self, idx: usize) -> Self {
+        self.insert(idx);
+        self
+    }
+
+    /// A copy with `idx` removed.
+    pub fn without(mut self, idx: usize) -> Self {
+        self.remove(idx);
+        self
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        (*self - *other).is_empty()
+    }
+
+    /// Members in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..B::CAPACITY).filter(move |&idx| self.bits.get(idx))
+    }
+
+    pub fn contains_letter(&self, letter: char) -> bool {
+        self.contains(letter_index(letter))
+    }
+
+    pub fn with_letter(self, letter: char) -> Self {
+        self.with(letter_index(letter))
+    }
+
+    pub fn without_letter(self, letter: char) -> Self {
+        self.without(letter_index(letter))
+    }
+
+    /// Members as lowercase letters, in order.
+    pub fn letters(&self) -> String {
+        self.iter().map(index_letter).collect()
+    }
+}
+
+impl<B: Bits> Default for BitSet<B> {
+    fn default() -> Self {
+        BitSet::new()
+    }
+}
+
+impl<B: Bits> fmt::Debug for BitSet<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<B: Bits> BitOr for BitSet<B> {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        BitSet::from_bits(self.bits.or(other.bits))
+    }
+}
+
+impl<B: Bits> BitAnd for BitSet<B> {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        BitSet::from_bits(self.bits.and(other.bits))
+    }
+}
+
+impl<B: Bits> Sub for BitSet<B> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        BitSet::from_bits(self.bits.and_not(other.bits))
+    }
+}
+
+impl<B: Bits> FromIterator<usize> for BitSet<B> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        iter.into_iter().fold(BitSet::new(), BitSet::with)
+    }
+}
+
+impl<B: Bits> FromIterator<char> for BitSet<B> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        iter.into_iter().map(letter_index).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letters() {
+        let empty = BitSet::<u32>::new();
+        assert_eq!(empty.letters(), "");
+        assert!(!empty.contains_letter('a'));
+
+        let ab = empty.with_letter('a').with_letter('B');
+        assert_eq!(ab.letters(), "ab");
+        assert_eq!(ab.without_letter('a').letters(), "b");
+        assert_eq!(ab - empty, ab);
+        assert_eq!(
+            "ab".chars().collect::<BitSet<u32>>() | "ac".chars().collect(),
+            "abc".chars().collect()
+        );
+        assert_eq!(format!("{:?}", ab), "{0, 1}");
+    }
+
+    #[test]
+    fn test_algebra() {
+        let a: BitSet = vec![1, 5, 63].into_iter().collect();
+        let b: BitSet = vec![5, 7].into_iter().collect();
+        assert_eq!((a | b).iter().collect::<Vec<_>>(), vec![1, 5, 7, 63]);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), vec![5]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), vec![1, 63]);
+        assert_eq!(a.len(), 3);
+        assert!((a & b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert!(!a.contains(64));
+        assert_eq!(a.without(1).without(5).without(63), BitSet::new());
+    }
+
+    #[test]
+    fn test_widths() {
+        let mut small = BitSet::<u32>::new();
+        small.insert(31);
+        assert_eq!(small.bits(), 1 << 31);
+        assert_eq!(small.capacity(), 32);
+
+        let wide: BitSet<u128> = vec![0, 100].into_iter().collect();
+        assert_eq!(wide.len(), 2);
+
+        let mut huge: BitSet<[u64; 4]> = vec![3, 64, 255].into_iter().collect();
+        assert_eq!(huge.capacity(), 256);
+        assert!(huge.contains(64) && !huge.contains(65));
+        huge.remove(64);
+        assert_eq!(huge.iter().collect::<Vec<_>>(), vec![3, 255]);
+        assert_eq!((huge | huge).len(), 2);
+    }
+}
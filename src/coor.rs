@@ -1,6 +1,11 @@
+use failure::{bail, err_msg, Error};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
-use std::ops::{Add, AddAssign};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
+/// A point on a 2d map, with `y` pointing down like the lines of a text map.
 #[derive(PartialEq, Eq, Default, Clone, Copy, Hash)]
 pub struct Coor {
     pub x: i64,
@@ -11,13 +16,56 @@ impl Coor {
     pub fn new(x: i64, y: i64) -> Self {
         Coor { x, y }
     }
+
+    pub fn manhattan(&self, other: Coor) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(&self, other: Coor) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// Up, right, down, left.
+    pub fn neighbours4(self) -> impl Iterator<Item = Coor> {
+        Direction::ALL.iter().map(move |d| self + d.offset())
+    }
+
+    /// Clockwise, starting top left.
+    pub fn neighbours8(self) -> impl Iterator<Item = Coor> {
+        [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+        ]
+        .iter()
+        .map(move |&(x, y)| self + Coor::new(x, y))
+    }
 }
+
 impl fmt::Debug for Coor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "({}, {})", self.x, self.y)
     }
 }
 
+/// Reading order: top to bottom, then left to right.
+impl Ord for Coor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Coor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Add for Coor {
     type Output = Self;
 
@@ -27,10 +75,238 @@ impl Add for Coor {
 }
 
 impl AddAssign for Coor {
-    // type Output = Self;
-
     fn add_assign(&mut self, other: Self) {
-        // Coor::new(self.x + other.x, self.y + other.y)
         *self = *self + other;
     }
 }
+
+impl Sub for Coor {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Coor::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Coor {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Coor {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Coor::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Coor {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Coor::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// The smallest rectangle containing some coors, edges included.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Bounds {
+    pub min: Coor,
+    pub max: Coor,
+}
+
+impl Bounds {
+    /// `None` if there are no coors.
+    pub fn of<'a, I>(coors: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Coor>,
+    {
+        let mut coors = coors.into_iter();
+        let first = *coors.next()?;
+        Some(coors.fold(Bounds::new(first, first), |b, c| b.extend(*c)))
+    }
+
+    pub fn new(min: Coor, max: Coor) -> Self {
+        Bounds { min, max }
+    }
+
+    pub fn extend(self, coor: Coor) -> Self {
+        Bounds::new(
+            Coor::new(self.min.x.min(coor.x), self.min.y.min(coor.y)),
+            Coor::new(self.max.x.max(coor.x), self.max.y.max(coor.y)),
+        )
+    }
+
+    pub fn contains(&self, coor: Coor) -> bool {
+        (self.min.x..=self.max.x).contains(&coor.x) && (self.min.y..=self.max.y).contains(&coor.y)
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.x - self.min.x + 1
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.y - self.min.y + 1
+    }
+
+    /// Every coor inside, in reading order.
+    pub fn iter(self) -> impl Iterator<Item = Coor> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Coor::new(x, y)))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_left(self) -> Self {
+        use Direction::*;
+        match self {
+            Up => Left,
+            Left => Down,
+            Down => Right,
+            Right => Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.turn_left().opposite()
+    }
+
+    pub fn opposite(self) -> Self {
+        use Direction::*;
+        match self {
+            Up => Down,
+            Down => Up,
+            Left => Right,
+            Right => Left,
+        }
+    }
+
+    pub fn offset(self) -> Coor {
+        use Direction::*;
+        match self {
+            Up => Coor::new(0, -1),
+            Down => Coor::new(0, 1),
+            Left => Coor::new(-1, 0),
+            Right => Coor::new(1, 0),
+        }
+    }
+}
+
+/// Accepts `UDLR`, `NSWE` (either case), `^v<>` and arrows.
+impl TryFrom<char> for Direction {
+    type Error = Error;
+
+    fn try_from(value: char) -> Result<Self, Error> {
+        use Direction::*;
+        match value {
+            'U' | 'u' | 'N' | 'n' | '^' | '↑' => Ok(Up),
+            'D' | 'd' | 'S' | 's' | 'v' | 'V' | '↓' => Ok(Down),
+            'L' | 'l' | 'W' | 'w' | '<' | '←' => Ok(Left),
+            'R' | 'r' | 'E' | 'e' | '>' | '→' => Ok(Right),
+            c => bail!("Invalid direction: {:?}", c),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::try_from(c),
+            _ => Err(err_msg(format!("Invalid direction: {:?}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Coor::new(3, -2);
+        let b = Coor::new(-1, 5);
+        assert_eq!(a - b, Coor::new(4, -7));
+        assert_eq!(-a, Coor::new(-3, 2));
+        assert_eq!(a * 3, Coor::new(9, -6));
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(a.chebyshev(b), 7);
+        let mut c = a;
+        c -= b;
+        c += b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let c = Coor::new(1, 1);
+        assert_eq!(
+            c.neighbours4().collect::<Vec<_>>(),
+            vec![
+                Coor::new(1, 0),
+                Coor::new(2, 1),
+                Coor::new(1, 2),
+                Coor::new(0, 1)
+            ]
+        );
+        assert_eq!(c.neighbours8().count(), 8);
+        assert!(c.neighbours8().all(|n| n.chebyshev(c) == 1));
+    }
+
+    #[test]
+    fn test_reading_order() {
+        let mut coors = vec![Coor::new(0, 1), Coor::new(1, 0), Coor::new(0, 0)];
+        coors.sort();
+        assert_eq!(
+            coors,
+            vec![Coor::new(0, 0), Coor::new(1, 0), Coor::new(0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(Bounds::of(&[]), None);
+        let bounds = Bounds::of(&[Coor::new(2, -1), Coor::new(0, 1)]).expect("not empty");
+        assert_eq!(bounds, Bounds::new(Coor::new(0, -1), Coor::new(2, 1)));
+        assert_eq!((bounds.width(), bounds.height()), (3, 3));
+        assert!(bounds.contains(Coor::new(1, 0)));
+        assert!(!bounds.contains(Coor::new(3, 0)));
+        assert_eq!(bounds.iter().count(), 9);
+        assert_eq!(bounds.iter().nth(3), Some(Coor::new(0, 0)));
+    }
+
+    #[test]
+    fn test_direction() -> Result<(), Error> {
+        use Direction::*;
+        assert_eq!(Up.turn_right(), Right);
+        assert_eq!(Up.turn_left(), Left);
+        assert_eq!(Left.opposite(), Right);
+        assert_eq!(Coor::default() + Up.offset(), Coor::new(0, -1));
+        for (s, d) in &[("U", Up), ("S", Down), ("<", Left), ("→", Right)] {
+            assert_eq!(s.parse::<Direction>()?, *d);
+        }
+        assert!("X".parse::<Direction>().is_err());
+        assert!("UU".parse::<Direction>().is_err());
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use super::Result;
+use crate::parse;
 use failure::{bail, err_msg, Error};
 use std::collections::{HashMap, VecDeque};
 use std::convert::TryFrom;
@@ -119,12 +120,8 @@ impl Cpu {
         }
     }
 
-    pub fn from_str(program_str: &str) -> Self {
-        let program: Vec<_> = program_str
-            .split(',')
-            .filter_map(|x| x.parse::<i64>().ok())
-            .collect();
-        Self::new(program)
+    pub fn from_str(program_str: &str) -> Result<Self> {
+        Ok(Self::new(parse::csv(program_str)?))
     }
 
     pub fn enqueue_input(&mut self, value: i64) {
@@ -320,7 +317,7 @@ mod tests {
 
     #[test]
     fn test_203() -> Result<()> {
-        let mut cpu = Cpu::from_str("203,10,99");
+        let mut cpu = Cpu::from_str("203,10,99")?;
         cpu.relative_base = 2;
         cpu.enqueue_input(1);
         assert_eq!(cpu.run()?, CpuState::Halted);
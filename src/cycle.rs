@@ -0,0 +1,228 @@
+use num::integer::lcm;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// States from index `start` on repeat every `length` steps.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The first index with the same state as index `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare. Only ever holds a few states, but `step` must
+/// eventually repeat.
+pub fn floyd<S, F>(start: S, mut step: F) -> Cycle
+where
+    S: PartialEq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let half = step(&hare);
+        hare = step(&half);
+    }
+
+    let mut cycle_start = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Brent's algorithm, like `floyd` with fewer steps.
+pub fn brent<S, F>(start: S, mut step: F) -> Cycle
+where
+    S: PartialEq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// The first state equal to an earlier one, remembering every state seen.
+pub fn first_repeat<S, I>(states: I) -> Option<(Cycle, S)>
+where
+    S: Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = HashMap::new();
+    for (idx, state) in states.into_iter().enumerate() {
+        if let Some(&first) = seen.get(&state) {
+            let cycle = Cycle {
+                start: first,
+                length: idx - first,
+            };
+            return Some((cycle, state));
+        }
+        seen.insert(state, idx);
+    }
+    None
+}
+
+/// The state after `n` steps, skipping whole cycles once one shows up.
+pub fn advance<S, F>(start: S, mut step: F, n: usize) -> S
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::new();
+    seen.insert(start.clone(), 0);
+    let mut history = vec![start];
+    while history.len() <= n {
+        let next = step(&history[history.len() - 1]);
+        if let Some(&first) = seen.get(&next) {
+            let cycle = Cycle {
+                start: first,
+                length: history.len() - first,
+            };
+            return history.swap_remove(cycle.reduce(n));
+        }
+        seen.insert(next.clone(), history.len());
+        history.push(next);
+    }
+    history.swap_remove(n)
+}
+
+/// Steps until `start` comes back. Never returns if `start` isn't on a
+/// cycle, which can't happen when every state has a single predecessor.
+pub fn period<S, F>(start: &S, mut step: F) -> usize
+where
+    S: PartialEq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut state = step(start);
+    let mut steps = 1;
+    while state != *start {
+        state = step(&state);
+        steps += 1;
+    }
+    steps
+}
+
+/// When independent parts repeat with these periods, the whole does with
+/// the result.
+pub fn combined_period<I: IntoIterator<Item = usize>>(periods: I) -> usize {
+    periods.into_iter().fold(1, lcm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 6, 3, 4, ...
+    fn step(&n: &u32) -> u32 {
+        if n == 6 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_floyd_and_brent() {
+        let expected = Cycle {
+            start: 3,
+            length: 4,
+        };
+        assert_eq!(floyd(0, step), expected);
+        assert_eq!(brent(0, step), expected);
+        assert_eq!(
+            floyd(4, step),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+        assert_eq!(
+            brent(5, |&n| n),
+            Cycle {
+                start: 0,
+                length: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_first_repeat() {
+        let states = std::iter::successors(Some(0), |n| Some(step(n)));
+        assert_eq!(
+            first_repeat(states),
+            Some((
+                Cycle {
+                    start: 3,
+                    length: 4
+                },
+                3
+            ))
+        );
+        assert_eq!(first_repeat(vec![1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_advance() {
+        assert_eq!(advance(0, step, 2), 2);
+        assert_eq!(advance(0, step, 7), 3);
+        assert_eq!(advance(0, step, 1_000_000_000), 4);
+        let cycle = floyd(0, step);
+        assert_eq!(cycle.reduce(1_000_000_000), 4);
+    }
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(period(&0, |n| (n + 1) % 6), 6);
+        assert_eq!(combined_period(vec![18, 28, 44]), 2772);
+    }
+}
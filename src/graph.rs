@@ -0,0 +1,158 @@
+use crate::coor::Coor;
+use crate::grid::Grid;
+use crate::search::{bfs, ByKey};
+use std::collections::{HashMap, HashSet};
+
+/// What a cell means for walking the grid.
+pub enum Cell<A> {
+    Blocked,
+    Open,
+    /// Open, and worth remembering when an edge crosses it.
+    Annotated(A),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Edge<A> {
+    pub to: Coor,
+    pub distance: usize,
+    /// Annotations of the cells in between, from the start of the edge.
+    pub crossed: Vec<A>,
+}
+
+/// Points of interest of a grid, connected by the corridors between them.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Graph<A> {
+    edges: HashMap<Coor, Vec<Edge<A>>>,
+}
+
+impl<A> Graph<A> {
+    pub fn nodes(&self) -> impl Iterator<Item = Coor> + '_ {
+        self.edges.keys().cloned()
+    }
+
+    /// Shortest first, ties in reading order.
+    pub fn edges(&self, from: Coor) -> &[Edge<A>] {
+        self.edges.get(&from).map_or(&[], |e| e.as_slice())
+    }
+
+    /// `(to, distance)` pairs, e.g. for `search::dijkstra`.
+    pub fn neighbours(&self, from: Coor) -> impl Iterator<Item = (Coor, usize)> + '_ {
+        self.edges(from).iter().map(|e| (e.to, e.distance))
+    }
+}
+
+/// Connects every cell matching `is_poi` to each point of interest it can
+/// reach without passing another one, by the shortest such path.
+pub fn compress<T, A, P, C>(grid: &Grid<T>, mut is_poi: P, cell: C) -> Graph<A>
+where
+    A: Clone,
+    P: FnMut(Coor, &T) -> bool,
+    C: Fn(&T) -> Cell<A>,
+{
+    let pois: HashSet<Coor> = grid
+        .iter()
+        .filter(|&(c, t)| is_poi(c, t))
+        .map(|(c, _)| c)
+        .collect();
+    let mut edges = HashMap::new();
+    for &from in &pois {
+        let outcome = bfs(
+            (from, vec![]),
+            ByKey::new(|(pos, _): &(Coor, Vec<A>)| *pos),
+            |(pos, crossed)| {
+                if *pos != from && pois.contains(pos) {
+                    return vec![];
+                }
+                grid.neighbours4(*pos)
+                    .filter_map(|(next, tile)| {
+                        let mut crossed = crossed.clone();
+                        match cell(tile) {
+                            Cell::Blocked => return None,
+                            Cell::Annotated(a) if !pois.contains(&next) => crossed.push(a),
+                            _ => {}
+                        }
+                        Some((next, crossed))
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |_| false,
+        );
+        let mut from_edges: Vec<Edge<A>> = outcome
+            .reached()
+            .filter(|((pos, _), _)| *pos != from && pois.contains(pos))
+            .map(|((to, crossed), distance)| Edge {
+                to: *to,
+                distance,
+                crossed: crossed.clone(),
+            })
+            .collect();
+        from_edges.sort_by_key(|e| (e.distance, e.to));
+        edges.insert(from, from_edges);
+    }
+    Graph { edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress() -> crate::Result<()> {
+        let grid = Grid::parse(
+            "\
+#########
+#a..A..b#
+###.#####
+###c#####
+#########",
+            Some,
+        )?;
+        let graph = compress(
+            &grid,
+            |_, &c| c.is_ascii_lowercase(),
+            |&c| match c {
+                '#' => Cell::Blocked,
+                c if c.is_ascii_uppercase() => Cell::Annotated(c),
+                _ => Cell::Open,
+            },
+        );
+        let (a, b, c) = (Coor::new(1, 1), Coor::new(7, 1), Coor::new(3, 3));
+        let mut nodes: Vec<_> = graph.nodes().collect();
+        nodes.sort();
+        assert_eq!(nodes, vec![a, b, c]);
+        assert_eq!(
+            graph.edges(a),
+            &[
+                Edge {
+                    to: c,
+                    distance: 4,
+                    crossed: vec![]
+                },
+                Edge {
+                    to: b,
+                    distance: 6,
+                    crossed: vec!['A']
+                },
+            ]
+        );
+        assert_eq!(
+            graph.neighbours(b).collect::<Vec<_>>(),
+            vec![(a, 6), (c, 6)]
+        );
+        assert!(graph.edges(Coor::new(0, 0)).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pois_block() -> crate::Result<()> {
+        // b is only reachable through c
+        let grid = Grid::parse("a.c.b", Some)?;
+        let graph = compress(&grid, |_, &c| c != '.', |_| Cell::<()>::Open);
+        assert_eq!(
+            graph.neighbours(Coor::new(0, 0)).collect::<Vec<_>>(),
+            vec![(Coor::new(2, 0), 2)]
+        );
+        assert_eq!(graph.edges(Coor::new(2, 0)).len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+use super::Result;
+use crate::coor::{Bounds, Coor};
+use crate::parse::{self, ParseError};
+use failure::bail;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Storage<T> {
+    /// Every coor in `bounds`, in reading order.
+    Dense {
+        bounds: Bounds,
+        cells: Vec<T>,
+    },
+    Sparse(HashMap<Coor, T>),
+}
+
+/// A 2d map. Dense grids cover a fixed rectangle, sparse ones hold any coors
+/// and have the bounding box of whatever is in them.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Grid<T> {
+    storage: Storage<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn sparse() -> Self {
+        Grid {
+            storage: Storage::Sparse(HashMap::new()),
+        }
+    }
+
+    pub fn dense(bounds: Bounds, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        let size = (bounds.width() * bounds.height()) as usize;
+        Grid {
+            storage: Storage::Dense {
+                bounds,
+                cells: vec![fill; size],
+            },
+        }
+    }
+
+    /// A dense grid from cells in reading order.
+    pub fn from_vec(bounds: Bounds, cells: Vec<T>) -> Result<Self> {
+        if cells.len() as i64 != bounds.width() * bounds.height() {
+            bail!(
+                "{} cells don't fit a {}x{} grid",
+                cells.len(),
+                bounds.width(),
+                bounds.height()
+            );
+        }
+        Ok(Grid {
+            storage: Storage::Dense { bounds, cells },
+        })
+    }
+
+    /// A dense grid from a rectangular text map, see `parse::grid`.
+    pub fn parse<F>(input: &str, tile: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let cells = parse::grid(input, tile)?;
+        let bounds = match Bounds::of(cells.iter().map(|(c, _)| c)) {
+            Some(bounds) => bounds,
+            None => bail!("empty map"),
+        };
+        for (number, line) in parse::lines(input) {
+            let width = line.chars().count() as i64;
+            if width != bounds.width() {
+                return Err(ParseError::new(
+                    number,
+                    1,
+                    &format!("expected {} columns, got {}", bounds.width(), width),
+                )
+                .into());
+            }
+        }
+        Grid::from_vec(bounds, cells.into_iter().map(|(_, t)| t).collect())
+    }
+
+    fn index(bounds: &Bounds, coor: Coor) -> Option<usize> {
+        if !bounds.contains(coor) {
+            return None;
+        }
+        Some(((coor.y - bounds.min.y) * bounds.width() + coor.x - bounds.min.x) as usize)
+    }
+
+    /// Fixed for dense grids, the bounding box of the contents for sparse ones.
+    pub fn bounds(&self) -> Option<Bounds> {
+        match &self.storage {
+            Storage::Dense { bounds, .. } => Some(*bounds),
+            Storage::Sparse(cells) => Bounds::of(cells.keys()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense { cells, .. } => cells.len(),
+            Storage::Sparse(cells) => cells.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, coor: Coor) -> Option<&T> {
+        match &self.storage {
+            Storage::Dense { bounds, cells } => Self::index(bounds, coor).map(|idx| &cells[idx]),
+            Storage::Sparse(cells) => cells.get(&coor),
+        }
+    }
+
+    pub fn get_mut(&mut self, coor: Coor) -> Option<&mut T> {
+        match &mut self.storage {
+            Storage::Dense { bounds, cells } => {
+                Self::index(bounds, coor).map(move |idx| &mut cells[idx])
+            }
+            Storage::Sparse(cells) => cells.get_mut(&coor),
+        }
+    }
+
+    pub fn contains(&self, coor: Coor) -> bool {
+        self.get(coor).is_some()
+    }
+
+    /// Returns the previous value. Panics outside a dense grid.
+    pub fn insert(&mut self, coor: Coor, value: T) -> Option<T> {
+        match &mut self.storage {
+            Storage::Dense { bounds, cells } => match Self::index(bounds, coor) {
+                Some(idx) => Some(std::mem::replace(&mut cells[idx], value)),
+                None => panic!("{:?} is outside the grid {:?}", coor, bounds),
+            },
+            Storage::Sparse(cells) => cells.insert(coor, value),
+        }
+    }
+
+    /// Reading order for dense grids, arbitrary for sparse ones.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Coor, &T)> + '_> {
+        match &self.storage {
+            Storage::Dense { bounds, cells } => Box::new(bounds.iter().zip(cells.iter())),
+            Storage::Sparse(cells) => Box::new(cells.iter().map(|(c, t)| (*c, t))),
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, t)| t)
+    }
+
+    /// The coors of cells matching `pred`.
+    pub fn find<'a, P>(&'a self, mut pred: P) -> impl Iterator<Item = Coor> + 'a
+    where
+        P: FnMut(&T) -> bool + 'a,
+    {
+        self.iter()
+            .filter_map(move |(c, t)| if pred(t) { Some(c) } else { None })
+    }
+
+    pub fn neighbours4(&self, coor: Coor) -> impl Iterator<Item = (Coor, &T)> {
+        coor.neighbours4()
+            .filter_map(move |n| self.get(n).map(|t| (n, t)))
+    }
+
+    pub fn neighbours8(&self, coor: Coor) -> impl Iterator<Item = (Coor, &T)> {
+        coor.neighbours8()
+            .filter_map(move |n| self.get(n).map(|t| (n, t)))
+    }
+
+    /// Row `y` across the bounding box, `None` for missing cells.
+    pub fn row(&self, y: i64) -> impl Iterator<Item = Option<&T>> {
+        let (min, max) = self.bounds().map_or((0, -1), |b| (b.min.x, b.max.x));
+        self.row_in(y, min, max)
+    }
+
+    /// Row `y` from `min` to `max`, so callers walking many rows only work
+    /// out the bounds once.
+    fn row_in(&self, y: i64, min: i64, max: i64) -> impl Iterator<Item = Option<&T>> {
+        (min..=max).map(move |x| self.get(Coor::new(x, y)))
+    }
+
+    /// Column `x` across the bounding box, `None` for missing cells.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = Option<&T>> {
+        let (min, max) = self.bounds().map_or((0, -1), |b| (b.min.y, b.max.y));
+        (min..=max).map(move |y| self.get(Coor::new(x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Vec<Option<&T>>> {
+        let bounds = self.bounds();
+        let (min_y, max_y) = bounds.map_or((0, -1), |b| (b.min.y, b.max.y));
+        let (min_x, max_x) = bounds.map_or((0, -1), |b| (b.min.x, b.max.x));
+        (min_y..=max_y).map(move |y| self.row_in(y, min_x, max_x).collect())
+    }
+
+    /// A grid of the same kind with `f` applied to every cell.
+    pub fn map<U, F>(&self, mut f: F) -> Grid<U>
+    where
+        F: FnMut(Coor, &T) -> U,
+    {
+        let storage = match &self.storage {
+            Storage::Dense { bounds, cells } => Storage::Dense {
+                bounds: *bounds,
+                cells: bounds
+                    .iter()
+                    .zip(cells.iter())
+                    .map(|(c, t)| f(c, t))
+                    .collect(),
+            },
+            Storage::Sparse(cells) => {
+                Storage::Sparse(cells.iter().map(|(c, t)| (*c, f(*c, t))).collect())
+            }
+        };
+        Grid { storage }
+    }
+
+    /// Moves every cell, keeping dense grids dense.
+    fn remap<F>(&self, f: F) -> Self
+    where
+        T: Clone,
+        F: Fn(Coor) -> Coor,
+    {
+        match &self.storage {
+            Storage::Dense { bounds, .. } => {
+                let corners = [f(bounds.min), f(bounds.max)];
+                let new_bounds = Bounds::of(&corners).expect("two corners");
+                let mut cells: Vec<Option<T>> = vec![None; self.len()];
+                for (coor, t) in self.iter() {
+                    let idx = Self::index(&new_bounds, f(coor)).expect("inside new bounds");
+                    cells[idx] = Some(t.clone());
+                }
+                Grid {
+                    storage: Storage::Dense {
+                        bounds: new_bounds,
+                        cells: cells
+                            .into_iter()
+                            .map(|t| t.expect("every cell moved"))
+                            .collect(),
+                    },
+                }
+            }
+            Storage::Sparse(cells) => cells.iter().map(|(c, t)| (f(*c), t.clone())).collect(),
+        }
+    }
+
+    /// Swaps x and y.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(|c| Coor::new(c.y, c.x))
+    }
+
+    /// Clockwise around the origin.
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(|c| Coor::new(-c.y, c.x))
+    }
+
+    /// Counter-clockwise around the origin.
+    pub fn rotate_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(|c| Coor::new(c.y, -c.x))
+    }
+
+    /// One line per row of the bounding box, each ending in `\n`.
+    pub fn render<F>(&self, mut tile: F) -> String
+    where
+        F: FnMut(Option<&T>) -> char,
+    {
+        let mut text = String::new();
+        for row in self.rows() {
+            text.extend(row.into_iter().map(&mut tile));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Collects into a sparse grid.
+impl<T> FromIterator<(Coor, T)> for Grid<T> {
+    fn from_iter<I: IntoIterator<Item = (Coor, T)>>(iter: I) -> Self {
+        Grid {
+            storage: Storage::Sparse(iter.into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(c: char) -> Option<bool> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+
+    fn draw(t: Option<&bool>) -> char {
+        match t {
+            Some(true) => '#',
+            Some(false) => '.',
+            None => ' ',
+        }
+    }
+
+    #[test]
+    fn test_parse_and_render() -> Result<()> {
+        let grid = Grid::parse("#..\n.##\n", wall)?;
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Coor::new(0, 0), Coor::new(2, 1)))
+        );
+        assert_eq!(grid.get(Coor::new(2, 1)), Some(&true));
+        assert_eq!(grid.get(Coor::new(3, 1)), None);
+        assert_eq!(grid.render(draw), "#..\n.##\n");
+
+        let e = Grid::parse("#..\n.#\n", wall)
+            .expect_err("ragged")
+            .downcast::<ParseError>()?;
+        assert_eq!(e.line, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut grid = Grid::sparse();
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.render(draw), "");
+        grid.insert(Coor::new(-1, 0), true);
+        grid.insert(Coor::new(1, 1), false);
+        assert_eq!(grid.insert(Coor::new(1, 1), true), Some(false));
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Coor::new(-1, 0), Coor::new(1, 1)))
+        );
+        assert_eq!(grid.render(draw), "#  \n  #\n");
+        assert_eq!(grid.len(), 2);
+    }
+
+    #[test]
+    fn test_neighbours() -> Result<()> {
+        let grid = Grid::parse("#.#\n.#.\n#.#", wall)?;
+        let centre = Coor::new(1, 1);
+        assert_eq!(grid.neighbours4(centre).filter(|(_, &t)| t).count(), 0);
+        assert_eq!(grid.neighbours8(centre).filter(|(_, &t)| t).count(), 4);
+        assert_eq!(grid.neighbours4(Coor::new(0, 0)).count(), 2);
+        assert_eq!(grid.find(|&t| t).count(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rows_and_columns() -> Result<()> {
+        let grid = Grid::parse("#..\n.##", wall)?;
+        assert_eq!(
+            grid.row(1).collect::<Vec<_>>(),
+            vec![Some(&false), Some(&true), Some(&true)]
+        );
+        assert_eq!(
+            grid.column(0).collect::<Vec<_>>(),
+            vec![Some(&true), Some(&false)]
+        );
+        assert_eq!(grid.rows().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform() -> Result<()> {
+        let grid = Grid::parse("#..\n.##", wall)?;
+        assert_eq!(grid.transpose().render(draw), "#.\n.#\n.#\n");
+        assert_eq!(grid.rotate_right().render(draw), ".#\n#.\n#.\n");
+        assert_eq!(grid.rotate_left().render(draw), ".#\n.#\n#.\n");
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+
+        let sparse: Grid<bool> = grid.iter().map(|(c, &t)| (c, t)).collect();
+        assert_eq!(
+            sparse.rotate_right().render(draw),
+            grid.rotate_right().render(draw)
+        );
+        assert_eq!(grid.map(|_, &t| !t).render(draw), ".##\n#..\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use super::Result;
+use failure::{bail, err_msg};
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+pub trait InputProvider {
+    fn fetch(&self, day: u32) -> Result<String>;
+}
+
+/// Reads `inputs/dayNN.txt` from a cache directory.
+pub struct FsProvider {
+    dir: PathBuf,
+}
+
+impl FsProvider {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        FsProvider {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.dir.join("inputs").join(format!("day{:02}.txt", day))
+    }
+
+    pub fn contains(&self, day: u32) -> bool {
+        self.path(day).is_file()
+    }
+
+    pub fn store(&self, day: u32, input: &str) -> Result<()> {
+        let path = self.path(day);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, input)?;
+        Ok(())
+    }
+}
+
+impl InputProvider for FsProvider {
+    fn fetch(&self, day: u32) -> Result<String> {
+        let path = self.path(day);
+        fs::read_to_string(&path)
+            .map_err(|e| err_msg(format!("can't read {}: {}", path.display(), e)))
+    }
+}
+
+/// Fetches `/2019/day/N/input` over plain http, e.g. from a local mirror
+/// or stand-in server. There is no tls support.
+pub struct HttpProvider {
+    host: String,
+    port: u16,
+    prefix: String,
+    session: Option<String>,
+}
+
+impl HttpProvider {
+    pub fn new(url: &str, session: Option<String>) -> Result<Self> {
+        let rest = match url.strip_prefix("http://") {
+            Some(rest) => rest,
+            None => bail!("only http:// urls are supported: {}", url),
+        };
+        let (authority, prefix) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], rest[idx..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rfind(':') {
+            Some(idx) => (&authority[..idx], authority[idx + 1..].parse()?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            bail!("missing host in url: {}", url);
+        }
+        Ok(HttpProvider {
+            host: host.into(),
+            port,
+            prefix: prefix.into(),
+            session,
+        })
+    }
+
+    pub fn path(&self, day: u32) -> String {
+        format!("{}/2019/day/{}/input", self.prefix, day)
+    }
+
+    fn get(&self, path: &str) -> Result<String> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+            path, self.host
+        );
+        if let Some(session) = &self.session {
+            request += &format!("Cookie: session={}\r\n", session);
+        }
+        request += "\r\n";
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        parse_response(&response)
+    }
+}
+
+fn parse_response(response: &str) -> Result<String> {
+    let split = response
+        .find("\r\n\r\n")
+        .ok_or(err_msg("malformed http response"))?;
+    let (head, body) = (&response[..split], &response[split + 4..]);
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or("");
+    match status.split(' ').nth(1) {
+        Some("200") => {}
+        _ => bail!("http request failed: {}", status),
+    }
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    if chunked {
+        decode_chunked(body)
+    } else {
+        Ok(body.into())
+    }
+}
+
+fn decode_chunked(mut body: &str) -> Result<String> {
+    let mut decoded = String::new();
+    loop {
+        let end = body.find("\r\n").ok_or(err_msg("malformed chunk"))?;
+        let size = usize::from_str_radix(body[..end].trim(), 16)?;
+        if size == 0 {
+            break Ok(decoded);
+        }
+        let start = end + 2;
+        decoded += body
+            .get(start..start + size)
+            .ok_or(err_msg("truncated chunk"))?;
+        body = body
+            .get(start + size + 2..)
+            .ok_or(err_msg("truncated chunk"))?;
+    }
+}
+
+impl InputProvider for HttpProvider {
+    fn fetch(&self, day: u32) -> Result<String> {
+        self.get(&self.path(day))
+    }
+}
+
+/// Serves from the cache, falling back to (and storing from) `remote`.
+pub struct CachingProvider<P> {
+    cache: FsProvider,
+    remote: P,
+}
+
+impl<P: InputProvider> CachingProvider<P> {
+    pub fn new(cache: FsProvider, remote: P) -> Self {
+        CachingProvider { cache, remote }
+    }
+}
+
+impl<P: InputProvider> InputProvider for CachingProvider<P> {
+    fn fetch(&self, day: u32) -> Result<String> {
+        if self.cache.contains(day) {
+            return self.cache.fetch(day);
+        }
+        let input = self.remote.fetch(day)?;
+        self.cache.store(day, &input)?;
+        Ok(input)
+    }
+}
+
+/// Uses `AOC_INPUT_URL` (and `AOC_SESSION`) to fetch missing inputs
+/// if set, otherwise only the cache directory.
+pub fn default_provider<P: AsRef<Path>>(cache_dir: P) -> Result<Box<dyn InputProvider>> {
+    let cache = FsProvider::new(cache_dir);
+    Ok(match env::var("AOC_INPUT_URL") {
+        Ok(url) => {
+            let remote = HttpProvider::new(&url, env::var("AOC_SESSION").ok())?;
+            Box::new(CachingProvider::new(cache, remote))
+        }
+        Err(_) => Box::new(cache),
+    })
+}
+
+/// Day number from a binary name like `day05` (or a test binary `day05-1a2b`).
+pub fn day_from_name(name: &str) -> Option<u32> {
+    let digits: String = name
+        .strip_prefix("day")?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+pub fn current_day() -> Result<u32> {
+    let exe = env::args().next().ok_or(err_msg("no program name"))?;
+    let name = Path::new(&exe)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    day_from_name(name).ok_or(err_msg(format!("can't tell day from {:?}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2019-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn serve_once(response: &'static str) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().expect("addr"));
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut request = vec![];
+            let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("read");
+                if line.trim_end().is_empty() {
+                    break;
+                }
+                request.push(line.trim_end().to_string());
+            }
+            stream.write_all(response.as_bytes()).expect("write");
+            request
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_day_from_name() {
+        assert_eq!(day_from_name("day05"), Some(5));
+        assert_eq!(day_from_name("day18-0123abcd"), Some(18));
+        assert_eq!(day_from_name("aoc"), None);
+    }
+
+    #[test]
+    fn test_fs_provider() -> Result<()> {
+        let dir = temp_dir("fs");
+        let provider = FsProvider::new(&dir);
+        assert!(provider.fetch(1).is_err());
+        provider.store(1, "12\n14\n")?;
+        assert_eq!(provider.path(1), dir.join("inputs").join("day01.txt"));
+        assert_eq!(provider.fetch(1)?, "12\n14\n");
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_provider() -> Result<()> {
+        let (url, server) = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n1,0,0");
+        let provider = HttpProvider::new(&url, Some("abc".into()))?;
+        assert_eq!(provider.fetch(2)?, "1,0,0");
+        let request = server.join().expect("server");
+        assert_eq!(request[0], "GET /2019/day/2/input HTTP/1.1");
+        assert!(request.contains(&"Cookie: session=abc".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_provider_error() -> Result<()> {
+        let (url, server) = serve_once("HTTP/1.1 404 Not Found\r\n\r\n");
+        let provider = HttpProvider::new(&url, None)?;
+        assert!(provider.fetch(3).is_err());
+        server.join().expect("server");
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunked() -> Result<()> {
+        assert_eq!(
+            parse_response(
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n12\n1\r\n2\r\n4\n\r\n0\r\n\r\n"
+            )?,
+            "12\n14\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_provider() -> Result<()> {
+        let dir = temp_dir("caching");
+        let (url, server) = serve_once("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n1969\n");
+        let provider = CachingProvider::new(FsProvider::new(&dir), HttpProvider::new(&url, None)?);
+        assert_eq!(provider.fetch(1)?, "1969\n");
+        server.join().expect("server");
+        // served from the cache now, the stand-in server is gone
+        assert_eq!(provider.fetch(1)?, "1969\n");
+        assert_eq!(FsProvider::new(&dir).fetch(1)?, "1969\n");
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,18 @@
+pub mod automaton;
+pub mod bitset;
 pub mod coor;
 pub mod cpu;
+pub mod cycle;
+pub mod graph;
+pub mod grid;
+pub mod input;
+pub mod memo;
+pub mod modular;
+pub mod parse;
+pub mod runner;
+pub mod samples;
+pub mod scaffold;
+pub mod search;
 pub mod shared;
-pub use crate::shared::{dispatch, Result};
+pub mod vector;
+pub use crate::shared::{dispatch, dispatch_modes, Options, Result};
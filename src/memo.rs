@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// A cache for a recursive function, along with the read-only context it
+/// needs, e.g. a parsed map. Pass it down the recursion instead of reaching
+/// for a global.
+pub struct Memo<'c, C: ?Sized, K, V> {
+    context: &'c C,
+    cache: HashMap<K, V>,
+    limit: Option<usize>,
+    stats: Stats,
+}
+
+impl<'c, C: ?Sized, K: Hash + Eq, V: Clone> Memo<'c, C, K, V> {
+    pub fn new(context: &'c C) -> Self {
+        Memo {
+            context,
+            cache: HashMap::new(),
+            limit: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Empties the cache whenever it would grow past `limit` entries.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn context(&self) -> &'c C {
+        self.context
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Forgets every value, keeping the stats.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    /// The cached value for `key`, or the one `compute` returns, which can
+    /// recurse through the memo it's given.
+    pub fn get<F>(&mut self, key: K, compute: F) -> V
+    where
+        K: Clone,
+        F: FnOnce(&mut Self, K) -> V,
+    {
+        self.get_by(key, K::clone, compute)
+    }
+
+    /// Like `get`, for arguments that include more than the key, e.g. data
+    /// that doesn't change the result.
+    pub fn get_by<A, P, F>(&mut self, args: A, key: P, compute: F) -> V
+    where
+        P: FnOnce(&A) -> K,
+        F: FnOnce(&mut Self, A) -> V,
+    {
+        let key = key(&args);
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self, args);
+        if self.limit.is_some_and(|limit| self.cache.len() >= limit) {
+            self.cache.clear();
+        }
+        self.cache.insert(key, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<(), u64, u64>, n: u64) -> u64 {
+        memo.get(n, |memo, n| {
+            if n < 2 {
+                n
+            } else {
+                fib(memo, n - 1) + fib(memo, n - 2)
+            }
+        })
+    }
+
+    #[test]
+    fn test_recursion() {
+        let mut memo = Memo::new(&());
+        assert_eq!(fib(&mut memo, 90), 2_880_067_194_370_816_120);
+        assert_eq!(
+            memo.stats(),
+            Stats {
+                hits: 88,
+                misses: 91
+            }
+        );
+        assert_eq!(memo.len(), 91);
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+
+    #[test]
+    fn test_context_and_key() {
+        // ways to make `amount` from coins, ignoring the path taken so far
+        fn ways(memo: &mut Memo<[u32], (usize, u32), u64>, coin: usize, amount: u32) -> u64 {
+            memo.get_by(
+                (coin, amount, vec![coin]),
+                |&(coin, amount, _)| (coin, amount),
+                |memo, (coin, amount, _path)| {
+                    let coins = memo.context();
+                    if amount == 0 {
+                        return 1;
+                    }
+                    if coin == coins.len() {
+                        return 0;
+                    }
+                    let mut total = ways(memo, coin + 1, amount);
+                    if coins[coin] <= amount {
+                        total += ways(memo, coin, amount - coins[coin]);
+                    }
+                    total
+                },
+            )
+        }
+        let coins = [1, 2, 5, 10, 20, 50, 100, 200];
+        let mut memo = Memo::new(&coins[..]);
+        assert_eq!(ways(&mut memo, 0, 100), 4563);
+        assert!(memo.stats().hits > 0);
+
+        let mut limited = Memo::new(&coins[..]).with_limit(100);
+        assert_eq!(ways(&mut limited, 0, 100), 4563);
+        assert!(limited.len() <= 100);
+        assert!(limited.stats().misses > memo.stats().misses);
+    }
+}
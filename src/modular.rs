@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn gcd_extended(a: i64, b: i64) -> (i64, i64, i64) {
+    if a == 0 {
+        return (b, 0, 1);
+    }
+    let (g, x, y) = gcd_extended(b % a, a);
+    (g, y - (b / a) * x, x)
+}
+
+/// An integer modulo `modulus`, always kept in `0..modulus`.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ModInt {
+    value: i64,
+    modulus: i64,
+}
+
+impl ModInt {
+    pub fn new(value: i64, modulus: i64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+        ModInt {
+            value: value.rem_euclid(modulus),
+            modulus,
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn modulus(&self) -> i64 {
+        self.modulus
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = ModInt::new(1, self.modulus);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// `None` unless the value and modulus are coprime.
+    pub fn inverse(self) -> Option<Self> {
+        let (g, x, _) = gcd_extended(self.value, self.modulus);
+        if g != 1 {
+            return None;
+        }
+        Some(ModInt::new(x, self.modulus))
+    }
+
+    fn check(&self, other: &Self) {
+        assert_eq!(self.modulus, other.modulus, "mixed moduli");
+    }
+}
+
+impl fmt::Debug for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Add for ModInt {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.check(&other);
+        ModInt::new(
+            ((self.value as i128 + other.value as i128) % self.modulus as i128) as i64,
+            self.modulus,
+        )
+    }
+}
+
+impl AddAssign for ModInt {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for ModInt {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl SubAssign for ModInt {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt::new(self.modulus - self.value, self.modulus)
+    }
+}
+
+impl Mul for ModInt {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.check(&other);
+        ModInt::new(
+            ((self.value as i128 * other.value as i128) % self.modulus as i128) as i64,
+            self.modulus,
+        )
+    }
+}
+
+impl MulAssign for ModInt {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+/// The map `x -> a * x + b` modulo some number.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Affine {
+    pub a: ModInt,
+    pub b: ModInt,
+}
+
+impl Affine {
+    pub fn new(a: i64, b: i64, modulus: i64) -> Self {
+        Affine {
+            a: ModInt::new(a, modulus),
+            b: ModInt::new(b, modulus),
+        }
+    }
+
+    pub fn identity(modulus: i64) -> Self {
+        Affine::new(1, 0, modulus)
+    }
+
+    pub fn modulus(&self) -> i64 {
+        self.a.modulus()
+    }
+
+    pub fn apply(&self, x: i64) -> i64 {
+        (self.a * ModInt::new(x, self.modulus()) + self.b).value()
+    }
+
+    /// This map followed by `next`.
+    pub fn then(&self, next: &Affine) -> Affine {
+        Affine {
+            a: next.a * self.a,
+            b: next.a * self.b + next.b,
+        }
+    }
+
+    /// `None` unless `a` is invertible.
+    pub fn invert(&self) -> Option<Affine> {
+        let a = self.a.inverse()?;
+        Some(Affine {
+            a,
+            b: -(a * self.b),
+        })
+    }
+
+    /// This map applied `times` times.
+    pub fn pow(&self, mut times: u64) -> Affine {
+        let mut result = Affine::identity(self.modulus());
+        let mut base = *self;
+        while times > 0 {
+            if times & 1 == 1 {
+                result = result.then(&base);
+            }
+            base = base.then(&base);
+            times >>= 1;
+        }
+        result
+    }
+}
+
+/// The smallest non-negative `x` with `x == r (mod m)` for every `(r, m)`,
+/// together with the combined modulus. Moduli needn't be coprime; `None` if
+/// a modulus isn't positive, the congruences contradict each other or the
+/// combined modulus doesn't fit in an i64.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut modulus: i64 = 1;
+    for &(r, m) in congruences {
+        if m < 1 {
+            return None;
+        }
+        let (g, p, _) = gcd_extended(modulus, m);
+        let diff = r as i128 - x;
+        if diff % g as i128 != 0 {
+            return None;
+        }
+        let step = (m / g) as i128;
+        let k = (diff / g as i128 % step * p as i128).rem_euclid(step);
+        x += modulus as i128 * k;
+        modulus = i64::try_from(modulus as i128 * step).ok()?;
+        x = x.rem_euclid(modulus as i128);
+    }
+    Some((x as i64, modulus))
+}
+
+/// The smallest `k` with `base^k == target (mod modulus)`, by baby-step
+/// giant-step. `base` must be coprime to `modulus`.
+pub fn discrete_log(base: i64, target: i64, modulus: i64) -> Option<u64> {
+    let base = ModInt::new(base, modulus);
+    let target = ModInt::new(target, modulus);
+    let steps = (modulus as f64).sqrt().ceil() as u64 + 1;
+
+    let mut baby = HashMap::new();
+    let mut power = ModInt::new(1, modulus);
+    for j in 0..steps {
+        baby.entry(power.value()).or_insert(j);
+        power *= base;
+    }
+
+    let giant = base.inverse()?.pow(steps);
+    let mut current = target;
+    for i in 0..steps {
+        if let Some(&j) = baby.get(&current.value()) {
+            return Some(i * steps + j);
+        }
+        current *= giant;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_int() {
+        let m = |v| ModInt::new(v, 8);
+        assert_eq!(m(5).inverse(), Some(m(5)));
+        assert_eq!(m(4).inverse(), None);
+        assert_eq!(m(-3), m(5));
+        assert_eq!(m(7) + m(3), m(2));
+        assert_eq!(m(2) - m(3), m(7));
+        assert_eq!(m(3).pow(3), m(3));
+        assert_eq!(ModInt::new(2, 1_000_000_007).pow(1_000_000_006).value(), 1);
+
+        // doesn't overflow near i64::MAX
+        let big = ModInt::new(i64::MAX - 1, i64::MAX);
+        assert_eq!((big * big).value(), 1);
+    }
+
+    #[test]
+    fn test_affine() {
+        let f = Affine::new(3, 4, 11);
+        let g = Affine::new(2, -1, 11);
+        assert_eq!(f.then(&g).apply(5), g.apply(f.apply(5)));
+        assert_eq!(f.invert().map(|i| i.apply(f.apply(7))), Some(7));
+        assert_eq!(Affine::new(2, 0, 4).invert(), None);
+
+        let mut x = 1;
+        for _ in 0..1000 {
+            x = f.apply(x);
+        }
+        assert_eq!(f.pow(1000).apply(1), x);
+        assert_eq!(f.pow(0), Affine::identity(11));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (0, 0)]), None);
+        assert_eq!(crt(&[(2, -3)]), None);
+
+        // the combined modulus would need more than 64 bits
+        let big = 4_294_967_311; // prime, just above 2^32
+        assert_eq!(crt(&[(1, big), (2, big + 2), (3, 5)]), None);
+        assert_eq!(crt(&[(1, big), (2, 3)]), Some((big + 1, 3 * big)));
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        assert_eq!(discrete_log(3, 13, 17), Some(4));
+        assert_eq!(discrete_log(2, 1, 13), Some(0));
+        assert_eq!(discrete_log(2, 0, 13), None);
+        let k = discrete_log(7, 5_764_801, 20_201_227).expect("exists");
+        assert_eq!(ModInt::new(7, 20_201_227).pow(k).value(), 5_764_801);
+    }
+}
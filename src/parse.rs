@@ -0,0 +1,324 @@
+use super::Result;
+use crate::coor::Coor;
+use failure::Error;
+use regex::{Captures, Regex};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Positions are 1-based, like an editor's.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: &str) -> Self {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Moves an error raised while parsing a single line to line `line`.
+fn at_line(error: Error, line: usize) -> Error {
+    match error.downcast::<ParseError>() {
+        Ok(e) => ParseError::new(line, e.column, &e.message).into(),
+        Err(e) => ParseError::new(line, 1, &e.to_string()).into(),
+    }
+}
+
+/// Numbered lines, ignoring trailing newlines and `\r`s.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .trim_end_matches(['\n', '\r'])
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r'))
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line))
+}
+
+pub fn parse_lines<'a, T, F>(input: &'a str, mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&'a str) -> Result<T>,
+{
+    lines(input)
+        .map(|(number, line)| parse(line).map_err(|e| at_line(e, number)))
+        .collect()
+}
+
+/// Like `parse_lines`, skipping blank lines, which still count towards the
+/// line numbers of errors.
+pub fn parse_nonblank_lines<'a, T, F>(input: &'a str, mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&'a str) -> Result<T>,
+{
+    lines(input)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| parse(line).map_err(|e| at_line(e, number)))
+        .collect()
+}
+
+fn value<T>(token: &str, column: usize) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let trimmed = token.trim_start();
+    let column = column + token.len() - trimmed.len();
+    trimmed.trim_end().parse().map_err(|e| {
+        ParseError::new(
+            1,
+            column,
+            &format!("invalid value {:?}: {}", trimmed.trim_end(), e),
+        )
+        .into()
+    })
+}
+
+/// Moves the column of an error from parsing part of a line `columns` to the right.
+pub fn shift<T>(res: Result<T>, columns: usize) -> Result<T> {
+    res.map_err(|error| match error.downcast::<ParseError>() {
+        Ok(e) => ParseError::new(e.line, e.column + columns, &e.message).into(),
+        Err(e) => ParseError::new(1, columns + 1, &e.to_string()).into(),
+    })
+}
+
+/// Parses each part of `line` between `separator`s with `parse`.
+pub fn split_with<T, F>(line: &str, separator: &str, mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> Result<T>,
+{
+    let mut offset = 0;
+    line.split(separator)
+        .map(|token| {
+            let res = shift(parse(token), offset);
+            offset += token.len() + separator.len();
+            res
+        })
+        .collect()
+}
+
+/// Values separated by `separator`, e.g. `5,-3,7` or `123-456`.
+pub fn separated<T>(line: &str, separator: char) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    split_with(line, separator.encode_utf8(&mut [0; 4]), |token| {
+        value(token, 1)
+    })
+}
+
+fn single_line<T>(input: &str, parse: impl FnMut(&str) -> Result<T>) -> Result<T> {
+    let mut parsed = parse_lines(input, parse)?;
+    if parsed.len() > 1 {
+        return Err(ParseError::new(2, 1, "expected a single line").into());
+    }
+    Ok(parsed.remove(0))
+}
+
+/// A single line of comma separated values.
+pub fn csv<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    single_line(input, |line| separated(line, ','))
+}
+
+/// One value per line.
+pub fn numbers<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_lines(input, |line| value(line, 1))
+}
+
+/// A single line of digits.
+pub fn digits<T: From<u8>>(input: &str) -> Result<Vec<T>> {
+    single_line(input, |line| {
+        line.chars()
+            .enumerate()
+            .map(|(idx, c)| match c.to_digit(10) {
+                Some(d) => Ok(T::from(d as u8)),
+                None => Err(ParseError::new(1, idx + 1, &format!("not a digit: {:?}", c)).into()),
+            })
+            .collect()
+    })
+}
+
+/// Maps every char of a text map, with `(0, 0)` in the top left. `tile`
+/// returning `None` is an error at that char.
+pub fn grid<T, F>(input: &str, mut tile: F) -> Result<Vec<(Coor, T)>>
+where
+    F: FnMut(char) -> Option<T>,
+{
+    let mut cells = vec![];
+    for (number, line) in lines(input) {
+        for (idx, c) in line.chars().enumerate() {
+            match tile(c) {
+                Some(t) => cells.push((Coor::new(idx as i64, number as i64 - 1), t)),
+                None => {
+                    return Err(ParseError::new(
+                        number,
+                        idx + 1,
+                        &format!("unexpected char {:?}", c),
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+    Ok(cells)
+}
+
+pub fn captures<'t>(re: &Regex, line: &'t str) -> Result<Captures<'t>> {
+    re.captures(line).ok_or_else(|| {
+        ParseError::new(1, 1, &format!("{:?} doesn't match {}", line, re.as_str())).into()
+    })
+}
+
+/// Parses capture group `group`, reporting errors at its position.
+pub fn field<T>(caps: &Captures, group: usize) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let m = caps
+        .get(group)
+        .ok_or_else(|| ParseError::new(1, 1, &format!("missing group {}", group)))?;
+    value(m.as_str(), m.start() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(res: Result<impl fmt::Debug>) -> ParseError {
+        res.expect_err("should fail")
+            .downcast::<ParseError>()
+            .expect("ParseError")
+    }
+
+    #[test]
+    fn test_numbers() -> Result<()> {
+        assert_eq!(numbers::<i32>("12\n-14\n\n")?, vec![12, -14]);
+        assert_eq!(
+            error(numbers::<i32>("12\n1x\n")),
+            ParseError {
+                line: 2,
+                column: 1,
+                message: "invalid value \"1x\": invalid digit found in string".into()
+            }
+        );
+        assert_eq!(error(numbers::<i32>("12\n\n14")).line, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nonblank_lines() -> Result<()> {
+        let parse = |line: &str| separated::<i32>(line, ',');
+        assert_eq!(
+            parse_nonblank_lines("\n1,2\n  \n3\n\n", parse)?,
+            vec![vec![1, 2], vec![3]]
+        );
+        let e = error(parse_nonblank_lines("\n\n1,2\n\n3,x", parse));
+        assert_eq!((e.line, e.column), (5, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv() -> Result<()> {
+        assert_eq!(csv::<i64>("1,-2, 3\n")?, vec![1, -2, 3]);
+        assert_eq!(error(csv::<i64>("1,2\n3")).line, 2);
+        let e = error(csv::<i64>("1,2,,4"));
+        assert_eq!((e.line, e.column), (1, 5));
+        let e = error(csv::<i64>("1,2,  x"));
+        assert_eq!((e.line, e.column), (1, 7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_separated() -> Result<()> {
+        assert_eq!(separated::<u32>("123-456", '-')?, vec![123, 456]);
+        assert_eq!(error(separated::<u32>("123-4x6", '-')).column, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_with() -> Result<()> {
+        let re = Regex::new(r"^(\w+) (\w+)$")?;
+        let pair = |s: &str| -> Result<(u8, char)> {
+            let caps = captures(&re, s)?;
+            Ok((field(&caps, 1)?, field(&caps, 2)?))
+        };
+        assert_eq!(
+            split_with("1 A, 2 B", ", ", pair)?,
+            vec![(1, 'A'), (2, 'B')]
+        );
+        assert_eq!(error(split_with("1 A, x B", ", ", pair)).column, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_digits() -> Result<()> {
+        assert_eq!(digits::<i64>("1203\n")?, vec![1, 2, 0, 3]);
+        assert_eq!(error(digits::<u8>("12a")).column, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_grid() -> Result<()> {
+        let wall = |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        };
+        assert_eq!(
+            grid("#.\n.#\n", wall)?,
+            vec![
+                (Coor::new(0, 0), true),
+                (Coor::new(1, 0), false),
+                (Coor::new(0, 1), false),
+                (Coor::new(1, 1), true),
+            ]
+        );
+        let e = error(grid("#.\n.?", wall));
+        assert_eq!((e.line, e.column), (2, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_captures() -> Result<()> {
+        let re = Regex::new(r"<x=(-?\w+), y=(-?\w+)>")?;
+        let res = parse_lines("<x=1, y=2>\n<x=3, y=b>", |line| {
+            let caps = captures(&re, line)?;
+            Ok((field::<i32>(&caps, 1)?, field::<i32>(&caps, 2)?))
+        });
+        let e = error(res);
+        assert_eq!((e.line, e.column), (2, 9));
+
+        let e = error(parse_lines("<x=1, y=2>\nx", |line| {
+            captures(&re, line).map(|_| ())
+        }));
+        assert_eq!((e.line, e.column), (2, 1));
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use super::Result;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct Task<T> {
+    pub label: String,
+    run: Box<dyn FnOnce() -> Result<T> + Send>,
+}
+
+impl<T> Task<T> {
+    pub fn new<F>(label: &str, run: F) -> Self
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        Task {
+            label: label.into(),
+            run: Box::new(run),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TaskResult<T> {
+    pub label: String,
+    pub elapsed: Duration,
+    /// Errors and panics both end up as messages.
+    pub result: std::result::Result<T, String>,
+}
+
+/// A snapshot handed to the progress callback whenever something changes,
+/// and periodically while tasks are running.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub total: usize,
+    pub finished: usize,
+    pub failed: usize,
+    pub running: Vec<(String, Duration)>,
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}/{}]", self.finished, self.total)?;
+        if self.failed > 0 {
+            write!(f, " {} failed", self.failed)?;
+        }
+        for (label, elapsed) in &self.running {
+            write!(f, " {} {:.1}s", label, elapsed.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+enum Event<T> {
+    Started(usize, Instant),
+    Finished(usize, TaskResult<T>),
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        format!("panicked: {}", s)
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        format!("panicked: {}", s)
+    } else {
+        "panicked".into()
+    }
+}
+
+fn execute<T>(task: Task<T>) -> TaskResult<T> {
+    let start = Instant::now();
+    let Task { label, run } = task;
+    let result = match panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(payload) => Err(panic_message(payload)),
+    };
+    TaskResult {
+        label,
+        elapsed: start.elapsed(),
+        result,
+    }
+}
+
+/// Runs `tasks` on `threads` worker threads. Results come back in the order
+/// of `tasks`, however they finish; a failing or panicking task doesn't
+/// stop the others.
+pub fn run_parallel<T, P>(
+    tasks: Vec<Task<T>>,
+    threads: usize,
+    mut progress: P,
+) -> Vec<TaskResult<T>>
+where
+    T: Send + 'static,
+    P: FnMut(&Progress),
+{
+    let total = tasks.len();
+    let labels: Vec<String> = tasks.iter().map(|t| t.label.clone()).collect();
+    let queue = Arc::new(Mutex::new(
+        tasks.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..threads.max(1).min(total.max(1)))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let next = queue.lock().expect("queue lock").pop_front();
+                let (idx, task) = match next {
+                    Some(entry) => entry,
+                    None => break,
+                };
+                if sender.send(Event::Started(idx, Instant::now())).is_err() {
+                    break;
+                }
+                if sender.send(Event::Finished(idx, execute(task))).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+
+    let mut results: Vec<Option<TaskResult<T>>> = (0..total).map(|_| None).collect();
+    let mut running: Vec<(usize, Instant)> = vec![];
+    let mut failed = 0;
+    let mut report = |running: &[(usize, Instant)], finished, failed| {
+        progress(&Progress {
+            total,
+            finished,
+            failed,
+            running: running
+                .iter()
+                .map(|&(idx, start)| (labels[idx].clone(), start.elapsed()))
+                .collect(),
+        })
+    };
+    let mut finished = 0;
+    while finished < total {
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(Event::Started(idx, start)) => running.push((idx, start)),
+            Ok(Event::Finished(idx, result)) => {
+                running.retain(|&(i, _)| i != idx);
+                if result.result.is_err() {
+                    failed += 1;
+                }
+                results[idx] = Some(result);
+                finished += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        report(&running, finished, failed);
+    }
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    results
+        .into_iter()
+        .zip(labels)
+        .map(|(result, label)| {
+            result.unwrap_or(TaskResult {
+                label,
+                elapsed: Duration::default(),
+                result: Err("worker died".into()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use failure::bail;
+
+    #[test]
+    fn test_run_parallel() {
+        let tasks = vec![
+            Task::new("slow", || {
+                thread::sleep(Duration::from_millis(50));
+                Ok(1)
+            }),
+            Task::new("error", || bail!("no input")),
+            Task::new("panic", || panic!("boom")),
+            Task::new("fast", || Ok(4)),
+        ];
+        let mut snapshots = vec![];
+        let results = run_parallel(tasks, 3, |p| snapshots.push(p.clone()));
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.label.as_str(), r.result.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("slow", Ok(1)),
+                ("error", Err("no input".into())),
+                ("panic", Err("panicked: boom".into())),
+                ("fast", Ok(4)),
+            ]
+        );
+        assert!(results[0].elapsed >= Duration::from_millis(50));
+
+        let last = snapshots.last().expect("progress reported");
+        assert_eq!((last.total, last.finished, last.failed), (4, 4, 2));
+        assert!(last.running.is_empty());
+        assert!(snapshots.iter().any(|p| !p.running.is_empty()));
+    }
+
+    #[test]
+    fn test_progress_display() {
+        let progress = Progress {
+            total: 50,
+            finished: 3,
+            failed: 1,
+            running: vec![
+                ("day18 p1".into(), Duration::from_millis(4200)),
+                ("day12 p2".into(), Duration::from_millis(1100)),
+            ],
+        };
+        assert_eq!(
+            format!("{}", progress),
+            "[3/50] 1 failed day18 p1 4.2s day12 p2 1.1s"
+        );
+    }
+
+    #[test]
+    fn test_run_parallel_empty() {
+        let results = run_parallel(Vec::<Task<()>>::new(), 4, |_| {});
+        assert!(results.is_empty());
+    }
+}
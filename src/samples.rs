@@ -0,0 +1,195 @@
+use super::Result;
+use crate::input::day_from_name;
+use failure::{bail, err_msg};
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A sample is `samples/dayNN/<name>.input` plus the expected answers in
+/// `<name>.part1` and/or `<name>.part2`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Sample {
+    pub name: String,
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+pub fn day_dir(root: &Path, day: u32) -> PathBuf {
+    root.join("samples").join(format!("day{:02}", day))
+}
+
+/// The days with a `samples/dayNN` directory, in order.
+pub fn days(root: &Path) -> Result<Vec<u32>> {
+    let dir = root.join("samples");
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut days = vec![];
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(day) = entry.file_name().to_str().and_then(day_from_name) {
+            days.push(day);
+        }
+    }
+    days.sort();
+    Ok(days)
+}
+
+fn read_expected(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(
+        fs::read_to_string(path)?.trim_end_matches('\n').into(),
+    ))
+}
+
+/// All samples for `day`, sorted by name. A missing directory has no samples.
+pub fn load(root: &Path, day: u32) -> Result<Vec<Sample>> {
+    let dir = day_dir(root, day);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut samples = vec![];
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("input") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(err_msg("invalid sample name"))?
+            .to_string();
+        let sample = Sample {
+            input: fs::read_to_string(&path)?.trim_end_matches('\n').into(),
+            part1: read_expected(&dir.join(format!("{}.part1", name)))?,
+            part2: read_expected(&dir.join(format!("{}.part2", name)))?,
+            name,
+        };
+        if sample.part1.is_none() && sample.part2.is_none() {
+            bail!("sample {} has no expected answers", path.display());
+        }
+        samples.push(sample);
+    }
+    samples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(samples)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Outcome {
+    pub name: String,
+    pub part: u8,
+    pub expected: String,
+    pub actual: std::result::Result<String, String>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.actual.as_ref() == Ok(&self.expected)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} part {}: ", self.name, self.part)?;
+        match &self.actual {
+            _ if self.passed() => write!(f, "ok"),
+            Ok(actual) => write!(f, "expected {:?}, got {:?}", self.expected, actual),
+            Err(e) => write!(f, "expected {:?}, failed with {}", self.expected, e),
+        }
+    }
+}
+
+fn check_part<T: Display>(
+    name: &str,
+    part: u8,
+    expected: &Option<String>,
+    input: &str,
+    func: &dyn Fn(&str) -> Result<T>,
+) -> Option<Outcome> {
+    let expected = expected.as_ref()?;
+    Some(Outcome {
+        name: name.into(),
+        part,
+        expected: expected.clone(),
+        actual: func(input)
+            .map(|res| format!("{}", res).trim_end_matches('\n').to_string())
+            .map_err(|e| e.to_string()),
+    })
+}
+
+pub fn check<S, T>(
+    samples: &[Sample],
+    part1: &dyn Fn(&str) -> Result<S>,
+    part2: &dyn Fn(&str) -> Result<T>,
+) -> Vec<Outcome>
+where
+    S: Display,
+    T: Display,
+{
+    samples
+        .iter()
+        .flat_map(|s| {
+            vec![
+                check_part(&s.name, 1, &s.part1, &s.input, part1),
+                check_part(&s.name, 2, &s.part2, &s.input, part2),
+            ]
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_load_and_check() -> Result<()> {
+        let root = env::temp_dir().join(format!("aoc2019-samples-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dir = day_dir(&root, 1);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("b.input"), "1969\n")?;
+        fs::write(dir.join("b.part1"), "654\n")?;
+        fs::write(dir.join("b.part2"), "1\n")?;
+        fs::write(dir.join("a.input"), "12")?;
+        fs::write(dir.join("a.part1"), "2")?;
+
+        let samples = load(&root, 1)?;
+        assert_eq!(
+            samples[0],
+            Sample {
+                name: "a".into(),
+                input: "12".into(),
+                part1: Some("2".into()),
+                part2: None
+            }
+        );
+        assert_eq!(samples[1].input, "1969");
+
+        let fuel = |input: &str| -> Result<i32> { Ok(input.parse::<i32>()? / 3 - 2) };
+        let fail = |_: &str| -> Result<i32> { bail!("nope") };
+        let outcomes = check(&samples, &fuel, &fail);
+        assert_eq!(
+            outcomes.iter().map(|o| o.passed()).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+        assert_eq!(
+            format!("{}", outcomes[2]),
+            "b part 2: expected \"1\", failed with nope"
+        );
+
+        assert_eq!(load(&root, 2)?, vec![]);
+        fs::create_dir_all(day_dir(&root, 12))?;
+        fs::create_dir_all(root.join("samples").join("notes"))?;
+        assert_eq!(days(&root)?, vec![1, 12]);
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
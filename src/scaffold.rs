@@ -0,0 +1,111 @@
+use super::Result;
+use failure::bail;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `{{day}}` becomes the zero-padded day (`05`), `{{day_number}}` the plain one.
+pub fn render(template: &str, day: u32) -> String {
+    template
+        .replace("{{day}}", &format!("{:02}", day))
+        .replace("{{day_number}}", &day.to_string())
+}
+
+/// `None` is the default `day.tpl`, a name like `intcode` is `intcode.tpl`
+/// and anything ending in `.tpl` is used as a path as is.
+pub fn template_path(root: &Path, name: Option<&str>) -> PathBuf {
+    match name {
+        None => root.join("day.tpl"),
+        Some(name) if name.ends_with(".tpl") => PathBuf::from(name),
+        Some(name) => root.join(format!("{}.tpl", name)),
+    }
+}
+
+pub fn day_paths(root: &Path, day: u32) -> (PathBuf, PathBuf, PathBuf) {
+    let name = format!("day{:02}", day);
+    (
+        root.join("src").join("bin").join(format!("{}.rs", name)),
+        root.join("inputs").join(format!("{}.txt", name)),
+        root.join("answers").join(format!("{}.txt", name)),
+    )
+}
+
+fn create_empty(path: &Path) -> Result<bool> {
+    if path.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, "")?;
+    Ok(true)
+}
+
+/// Renders `template` into `src/bin/dayNN.rs` and creates empty input and
+/// answers files. Returns the files created; existing inputs and answers are
+/// left alone but an existing solution is an error.
+pub fn new_day(root: &Path, day: u32, template: &Path) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        bail!("invalid day: {}", day);
+    }
+    let (source, input, answers) = day_paths(root, day);
+    if source.exists() {
+        bail!("{} already exists", source.display());
+    }
+    let rendered = render(&fs::read_to_string(template)?, day);
+    fs::write(&source, rendered)?;
+
+    let mut created = vec![source];
+    for path in [input, answers].iter() {
+        if create_empty(path)? {
+            created.push(path.clone());
+        }
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("day{{day}}: {{day_number}}", 5),
+            "day05: 5".to_string()
+        );
+    }
+
+    #[test]
+    fn test_new_day() -> Result<()> {
+        let root = env::temp_dir().join(format!("aoc2019-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src").join("bin"))?;
+        fs::write(root.join("day.tpl"), "// day {{day_number}}\n")?;
+        fs::create_dir_all(root.join("inputs"))?;
+        fs::write(root.join("inputs").join("day07.txt"), "3,0,99")?;
+
+        let template = template_path(&root, None);
+        let created = new_day(&root, 7, &template)?;
+        let (source, input, answers) = day_paths(&root, 7);
+        assert_eq!(created, vec![source.clone(), answers.clone()]);
+        assert_eq!(fs::read_to_string(&source)?, "// day 7\n");
+        assert_eq!(fs::read_to_string(&input)?, "3,0,99");
+        assert_eq!(fs::read_to_string(&answers)?, "");
+
+        assert!(new_day(&root, 7, &template).is_err());
+        assert!(new_day(&root, 26, &template).is_err());
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_templates_render() -> Result<()> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for name in &[None, Some("intcode")] {
+            let template = fs::read_to_string(template_path(root, *name))?;
+            assert!(render(&template, 9).contains("dispatch(&part1, &part2)"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Decides which states get searched. Searches only expand states for which
+/// `insert` returns `true`.
+pub trait Visited<S> {
+    /// `false` if `state` counts as seen already.
+    fn insert(&mut self, state: &S) -> bool;
+}
+
+impl<S: Hash + Eq + Clone> Visited<S> for HashSet<S> {
+    fn insert(&mut self, state: &S) -> bool {
+        HashSet::insert(self, state.clone())
+    }
+}
+
+/// Considers states with the same key seen, e.g. to carry along data that
+/// shouldn't make a state new.
+pub struct ByKey<F, K> {
+    key: F,
+    seen: HashSet<K>,
+}
+
+impl<F, K> ByKey<F, K> {
+    pub fn new(key: F) -> Self {
+        ByKey {
+            key,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl<S, F, K> Visited<S> for ByKey<F, K>
+where
+    F: Fn(&S) -> K,
+    K: Hash + Eq,
+{
+    fn insert(&mut self, state: &S) -> bool {
+        self.seen.insert((self.key)(state))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// States whose neighbours were generated.
+    pub expanded: usize,
+    pub frontier_peak: usize,
+}
+
+struct Node<S, C> {
+    state: S,
+    parent: Option<usize>,
+    cost: C,
+    /// The cost is final.
+    settled: bool,
+}
+
+pub struct Outcome<S, C> {
+    nodes: Vec<Node<S, C>>,
+    goal: Option<usize>,
+    pub stats: Stats,
+}
+
+impl<S, C: Copy> Outcome<S, C> {
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.map(|idx| &self.nodes[idx].state)
+    }
+
+    pub fn cost(&self) -> Option<C> {
+        self.goal.map(|idx| self.nodes[idx].cost)
+    }
+
+    /// From the start to the goal, both included.
+    pub fn path(&self) -> Option<Vec<&S>> {
+        let mut path = vec![];
+        let mut idx = self.goal;
+        while let Some(i) = idx {
+            path.push(&self.nodes[i].state);
+            idx = self.nodes[i].parent;
+        }
+        if path.is_empty() {
+            return None;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every state with a known shortest cost.
+    pub fn reached(&self) -> impl Iterator<Item = (&S, C)> {
+        self.nodes
+            .iter()
+            .filter(|n| n.settled)
+            .map(|n| (&n.state, n.cost))
+    }
+}
+
+/// Breadth first from `start`, stopping at the first state matching
+/// `is_goal`. Costs are step counts.
+pub fn bfs<S, V, N, I, G>(
+    start: S,
+    mut visited: V,
+    mut neighbours: N,
+    mut is_goal: G,
+) -> Outcome<S, usize>
+where
+    V: Visited<S>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut outcome = Outcome {
+        nodes: vec![],
+        goal: None,
+        stats: Stats::default(),
+    };
+    let mut queue = VecDeque::new();
+    visited.insert(&start);
+    outcome.nodes.push(Node {
+        state: start,
+        parent: None,
+        cost: 0,
+        settled: true,
+    });
+    queue.push_back(0);
+
+    while let Some(idx) = queue.pop_front() {
+        if is_goal(&outcome.nodes[idx].state) {
+            outcome.goal = Some(idx);
+            break;
+        }
+        outcome.stats.expanded += 1;
+        let cost = outcome.nodes[idx].cost + 1;
+        for next in neighbours(&outcome.nodes[idx].state) {
+            if !visited.insert(&next) {
+                continue;
+            }
+            outcome.nodes.push(Node {
+                state: next,
+                parent: Some(idx),
+                cost,
+                settled: true,
+            });
+            queue.push_back(outcome.nodes.len() - 1);
+        }
+        outcome.stats.frontier_peak = outcome.stats.frontier_peak.max(queue.len());
+    }
+    outcome
+}
+
+/// Cheapest first, with `neighbours` returning each next state and the cost
+/// of getting there. `C::default()` must be zero.
+pub fn dijkstra<S, C, V, N, I, G>(start: S, visited: V, neighbours: N, is_goal: G) -> Outcome<S, C>
+where
+    C: Copy + Ord + Default + Add<Output = C>,
+    V: Visited<S>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, visited, neighbours, |_| C::default(), is_goal)
+}
+
+/// Like `dijkstra`, expanding states in order of cost plus `heuristic`. States
+/// are settled the first time they're expanded, so the heuristic must be
+/// consistent, never dropping by more than a step costs, and not just never
+/// overestimate the remaining cost.
+pub fn astar<S, C, V, N, I, H, G>(
+    start: S,
+    mut visited: V,
+    mut neighbours: N,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Outcome<S, C>
+where
+    C: Copy + Ord + Default + Add<Output = C>,
+    V: Visited<S>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    let mut outcome = Outcome {
+        nodes: vec![],
+        goal: None,
+        stats: Stats::default(),
+    };
+    let mut frontier = BinaryHeap::new();
+    frontier.push(Reverse((heuristic(&start), 0)));
+    outcome.nodes.push(Node {
+        state: start,
+        parent: None,
+        cost: C::default(),
+        settled: false,
+    });
+
+    while let Some(Reverse((_, idx))) = frontier.pop() {
+        if !visited.insert(&outcome.nodes[idx].state) {
+            continue;
+        }
+        outcome.nodes[idx].settled = true;
+        if is_goal(&outcome.nodes[idx].state) {
+            outcome.goal = Some(idx);
+            break;
+        }
+        outcome.stats.expanded += 1;
+        let cost = outcome.nodes[idx].cost;
+        for (next, step) in neighbours(&outcome.nodes[idx].state) {
+            let estimate = cost + step + heuristic(&next);
+            outcome.nodes.push(Node {
+                state: next,
+                parent: Some(idx),
+                cost: cost + step,
+                settled: false,
+            });
+            frontier.push(Reverse((estimate, outcome.nodes.len() - 1)));
+        }
+        outcome.stats.frontier_peak = outcome.stats.frontier_peak.max(frontier.len());
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coor::Coor;
+    use crate::grid::Grid;
+
+    fn maze() -> Grid<bool> {
+        Grid::parse(
+            "\
+.....
+.###.
+...#.
+.#.#.
+.#...",
+            |c| Some(c == '.'),
+        )
+        .expect("valid maze")
+    }
+
+    fn open_neighbours(grid: &Grid<bool>, c: Coor) -> Vec<Coor> {
+        grid.neighbours4(c)
+            .filter(|(_, &open)| open)
+            .map(|(n, _)| n)
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let end = Coor::new(2, 4);
+        let outcome = bfs(
+            Coor::new(0, 0),
+            HashSet::new(),
+            |&c| open_neighbours(&grid, c),
+            |&c| c == end,
+        );
+        assert_eq!(outcome.cost(), Some(6));
+        let path = outcome.path().expect("found");
+        assert_eq!(path.len(), 7);
+        assert_eq!(path[0], &Coor::new(0, 0));
+        assert_eq!(path[6], &end);
+        assert!(path.windows(2).all(|w| w[0].manhattan(*w[1]) == 1));
+        assert!(outcome.stats.expanded > 0);
+        assert!(outcome.stats.frontier_peak > 0);
+
+        let flood = bfs(
+            Coor::new(0, 0),
+            HashSet::new(),
+            |&c| open_neighbours(&grid, c),
+            |_| false,
+        );
+        assert_eq!(flood.goal(), None);
+        assert_eq!(flood.reached().count(), 18);
+        assert_eq!(flood.reached().map(|(_, d)| d).max(), Some(8));
+    }
+
+    #[test]
+    fn test_by_key() {
+        // count the steps taken, without them making a state new
+        let outcome = bfs(
+            (0, 0),
+            ByKey::new(|&(n, _): &(i32, i32)| n),
+            |&(n, steps)| vec![(n + 1, steps + 1), (n * 2, steps + 1)],
+            |&(n, _)| n == 10,
+        );
+        assert_eq!(outcome.goal(), Some(&(10, 5)));
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        // going right is expensive
+        let grid = maze();
+        let end = Coor::new(4, 4);
+        let neighbours = |&c: &Coor| {
+            open_neighbours(&grid, c)
+                .into_iter()
+                .map(move |n| (n, if n.x > c.x { 3 } else { 1 }))
+        };
+        let outcome = dijkstra(Coor::new(0, 0), HashSet::new(), neighbours, |&c| c == end);
+        assert_eq!(outcome.cost(), Some(16));
+
+        let guided = astar(
+            Coor::new(0, 0),
+            HashSet::new(),
+            neighbours,
+            |c| c.manhattan(end),
+            |&c| c == end,
+        );
+        assert_eq!(guided.cost(), Some(16));
+        assert!(guided.stats.expanded <= outcome.stats.expanded);
+        assert_eq!(guided.path().map(|p| p.len()), Some(9));
+    }
+}
@@ -1,9 +1,15 @@
+use crate::input::{current_day, default_provider};
+use crate::samples;
 use clap::{App, Arg};
-use failure::{bail, Error};
+use failure::{bail, format_err, Error};
+use std::collections::HashMap;
+use std::env;
 use std::fmt::Display;
 use std::fs::File;
 use std::io::{self, Read};
+use std::path::PathBuf;
 use std::result;
+use std::str::FromStr;
 
 pub type Result<T> = result::Result<T, Error>;
 
@@ -15,11 +21,53 @@ enum Part {
 enum Source {
     Stdin,
     File(String),
+    Default(String),
 }
 
 struct Args {
     part: Part,
     source: Source,
+    samples: bool,
+    mode: Option<String>,
+    options: Options,
+}
+
+/// `-o key=value` settings for a mode.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    pub fn parse<'a, I: IntoIterator<Item = &'a str>>(pairs: I) -> Result<Self> {
+        let mut values = HashMap::new();
+        for pair in pairs {
+            let split = match pair.find('=') {
+                Some(split) => split,
+                None => bail!("expected key=value, got {:?}", pair),
+            };
+            values.insert(pair[..split].into(), pair[split + 1..].into());
+        }
+        Ok(Options { values })
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// The value for `key`, or `default` if it wasn't given.
+    pub fn get<T>(&self, key: &str, default: T) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get_str(key) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|e| format_err!("bad value {:?} for {}: {}", value, key, e)),
+        }
+    }
 }
 
 fn read_stdin() -> Result<String> {
@@ -45,10 +93,36 @@ fn parse_input() -> Result<Args> {
                 .default_value("1")
                 .possible_values(&["1", "2"]),
         )
+        .arg(
+            Arg::with_name("cache-dir")
+                .long("cache-dir")
+                .help("Directory holding inputs/dayNN.txt, used when no input is given")
+                .takes_value(true)
+                .env("AOC_CACHE_DIR")
+                .default_value("."),
+        )
+        .arg(
+            Arg::with_name("samples")
+                .long("samples")
+                .help("Runs both parts against samples/dayNN/ instead of the input"),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .long("mode")
+                .help("Runs one of the day's extra modes instead of a part")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("option")
+                .short("o")
+                .help("Sets an option for the mode, as key=value")
+                .takes_value(true)
+                .number_of_values(1)
+                .multiple(true),
+        )
         .arg(
             Arg::with_name("input")
                 .help("Sets the input file to use, or `-` for stdin")
-                .required(true)
                 .index(1),
         )
         .get_matches();
@@ -58,28 +132,90 @@ fn parse_input() -> Result<Args> {
         "2" => Part::Part2,
         _ => bail!("Invalid part"),
     };
-    let source = match matches
-        .value_of("input")
-        .expect("input is required but missing")
-    {
-        "-" => Source::Stdin,
-        filename => Source::File(filename.into()),
+    let source = match matches.value_of("input") {
+        Some("-") => Source::Stdin,
+        Some(filename) => Source::File(filename.into()),
+        None => Source::Default(
+            matches
+                .value_of("cache-dir")
+                .expect("cache-dir has a default")
+                .into(),
+        ),
     };
-    Ok(Args { part, source })
+    Ok(Args {
+        part,
+        source,
+        samples: matches.is_present("samples"),
+        mode: matches.value_of("mode").map(String::from),
+        options: Options::parse(matches.values_of("option").into_iter().flatten())?,
+    })
 }
 
-type DayFunc<T> = dyn Fn(&str) -> Result<T>;
+pub type DayFunc<T> = dyn Fn(&str) -> Result<T>;
+
+/// Takes the input and the mode's options, returns what to print.
+pub type ModeFunc = dyn Fn(&str, &Options) -> Result<String>;
 
-fn run<S, T>(part1: &DayFunc<S>, part2: &DayFunc<T>) -> Result<String>
+fn samples_root() -> PathBuf {
+    env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
+
+fn run_samples<S, T>(part1: &DayFunc<S>, part2: &DayFunc<T>) -> Result<String>
+where
+    S: Display,
+    T: Display,
+{
+    let day = current_day()?;
+    let samples = samples::load(&samples_root(), day)?;
+    let outcomes = samples::check(&samples, part1, part2);
+    let report = outcomes
+        .iter()
+        .map(|o| format!("{}", o))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let failed = outcomes.iter().filter(|o| !o.passed()).count();
+    if failed > 0 {
+        bail!(
+            "{}\n{} of {} samples failed",
+            report,
+            failed,
+            outcomes.len()
+        );
+    }
+    Ok(format!("{}\n{} samples passed", report, outcomes.len()))
+}
+
+fn run<S, T>(part1: &DayFunc<S>, part2: &DayFunc<T>, modes: &[(&str, &ModeFunc)]) -> Result<String>
 where
     S: Display,
     T: Display,
 {
     let args = parse_input()?;
+    if args.samples {
+        return run_samples(part1, part2);
+    }
     let input = match args.source {
         Source::Stdin => read_stdin(),
         Source::File(filename) => read_file(&filename),
+        Source::Default(cache_dir) => default_provider(cache_dir)?.fetch(current_day()?),
     }?;
+    if let Some(name) = args.mode {
+        let mode = match modes.iter().find(|(n, _)| *n == name) {
+            Some((_, mode)) => mode,
+            None if modes.is_empty() => bail!("this day has no modes"),
+            None => {
+                let names: Vec<_> = modes.iter().map(|(n, _)| *n).collect();
+                bail!(
+                    "unknown mode {:?}, expected one of: {}",
+                    name,
+                    names.join(", ")
+                );
+            }
+        };
+        return mode(&input, &args.options);
+    }
     match args.part {
         Part::Part1 => part1(&input).map(|res| format!("{}", res)),
         Part::Part2 => part2(&input).map(|res| format!("{}", res)),
@@ -91,7 +227,37 @@ where
     S: Display,
     T: Display,
 {
-    let result = run(part1, part2)?;
+    dispatch_modes(part1, part2, &[])
+}
+
+/// Like `dispatch`, also offering `modes` by name through `--mode`.
+pub fn dispatch_modes<S, T>(
+    part1: &DayFunc<S>,
+    part2: &DayFunc<T>,
+    modes: &[(&str, &ModeFunc)],
+) -> Result<()>
+where
+    S: Display,
+    T: Display,
+{
+    let result = run(part1, part2, modes)?;
     println!("{}", result);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options() -> Result<()> {
+        let options = Options::parse(vec!["target=FUEL", "amount=3", "empty="])?;
+        assert_eq!(options.get_str("target"), Some("FUEL"));
+        assert_eq!(options.get("amount", 1)?, 3);
+        assert_eq!(options.get("missing", 7)?, 7);
+        assert_eq!(options.get_str("empty"), Some(""));
+        assert!(options.get::<i64>("target", 0).is_err());
+        assert!(Options::parse(vec!["oops"]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A point or offset with `N` integer axes.
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Vector<const N: usize>(pub [i64; N]);
+
+pub type Vector3 = Vector<3>;
+
+impl<const N: usize> Vector<N> {
+    pub fn new(axes: [i64; N]) -> Self {
+        Vector(axes)
+    }
+
+    pub fn zero() -> Self {
+        Vector([0; N])
+    }
+
+    pub fn axes(&self) -> &[i64; N] {
+        &self.0
+    }
+
+    /// Just axis `axis`, e.g. to simulate axes that don't interact separately.
+    pub fn project(&self, axis: usize) -> Vector<1> {
+        Vector([self.0[axis]])
+    }
+
+    pub fn manhattan(&self, other: Self) -> i64 {
+        (*self - other).0.iter().map(|a| a.abs()).sum()
+    }
+
+    /// Manhattan distance from the origin.
+    pub fn norm(&self) -> i64 {
+        self.manhattan(Vector::zero())
+    }
+
+    /// -1, 0 or 1 per axis.
+    pub fn signum(&self) -> Self {
+        self.map(i64::signum)
+    }
+
+    pub fn map<F: Fn(i64) -> i64>(&self, f: F) -> Self {
+        let mut axes = self.0;
+        for a in axes.iter_mut() {
+            *a = f(*a);
+        }
+        Vector(axes)
+    }
+
+    fn zip<F: Fn(i64, i64) -> i64>(self, other: Self, f: F) -> Self {
+        let mut axes = self.0;
+        for (a, b) in axes.iter_mut().zip(other.0.iter()) {
+            *a = f(*a, *b);
+        }
+        Vector(axes)
+    }
+}
+
+impl<const N: usize> Default for Vector<N> {
+    fn default() -> Self {
+        Vector::zero()
+    }
+}
+
+impl<const N: usize> fmt::Debug for Vector<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<")?;
+        for (idx, a) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", a)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl<const N: usize> Index<usize> for Vector<N> {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &i64 {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Vector<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> Add for Vector<N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.zip(other, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> AddAssign for Vector<N> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<const N: usize> Sub for Vector<N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.zip(other, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> SubAssign for Vector<N> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<const N: usize> Neg for Vector<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|a| -a)
+    }
+}
+
+impl<const N: usize> Mul<i64> for Vector<N> {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        self.map(|a| a * factor)
+    }
+}
+
+/// Like `Vector`, with the number of axes only known at runtime, e.g. from
+/// the input. Combining vectors with different numbers of axes panics.
+#[derive(PartialEq, Eq, Clone, Hash, Default)]
+pub struct DynVector(pub Vec<i64>);
+
+impl DynVector {
+    pub fn new(axes: Vec<i64>) -> Self {
+        DynVector(axes)
+    }
+
+    pub fn zero(dimensions: usize) -> Self {
+        DynVector(vec![0; dimensions])
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn axes(&self) -> &[i64] {
+        &self.0
+    }
+
+    /// Just axis `axis`, e.g. to simulate axes that don't interact separately.
+    pub fn project(&self, axis: usize) -> Vector<1> {
+        Vector([self.0[axis]])
+    }
+
+    pub fn manhattan(&self, other: &Self) -> i64 {
+        (self - other).norm()
+    }
+
+    /// Manhattan distance from the origin.
+    pub fn norm(&self) -> i64 {
+        self.0.iter().map(|a| a.abs()).sum()
+    }
+
+    /// -1, 0 or 1 per axis.
+    pub fn signum(&self) -> Self {
+        self.map(i64::signum)
+    }
+
+    pub fn map<F: Fn(i64) -> i64>(&self, f: F) -> Self {
+        DynVector(self.0.iter().map(|&a| f(a)).collect())
+    }
+
+    fn zip_with<F: Fn(&mut i64, i64)>(&mut self, other: &Self, f: F) {
+        assert_eq!(self.dimensions(), other.dimensions(), "different axes");
+        for (a, &b) in self.0.iter_mut().zip(other.0.iter()) {
+            f(a, b);
+        }
+    }
+}
+
+impl<const N: usize> From<Vector<N>> for DynVector {
+    fn from(vector: Vector<N>) -> Self {
+        DynVector(vector.0.to_vec())
+    }
+}
+
+impl fmt::Debug for DynVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<")?;
+        for (idx, a) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", a)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl Index<usize> for DynVector {
+    type Output = i64;
+
+    fn index(&self, axis: usize) -> &i64 {
+        &self.0[axis]
+    }
+}
+
+impl IndexMut<usize> for DynVector {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.0[axis]
+    }
+}
+
+impl Add for &DynVector {
+    type Output = DynVector;
+
+    fn add(self, other: Self) -> DynVector {
+        let mut sum = self.clone();
+        sum += other;
+        sum
+    }
+}
+
+impl AddAssign<&DynVector> for DynVector {
+    fn add_assign(&mut self, other: &DynVector) {
+        self.zip_with(other, |a, b| *a += b);
+    }
+}
+
+impl Sub for &DynVector {
+    type Output = DynVector;
+
+    fn sub(self, other: Self) -> DynVector {
+        let mut difference = self.clone();
+        difference -= other;
+        difference
+    }
+}
+
+impl SubAssign<&DynVector> for DynVector {
+    fn sub_assign(&mut self, other: &DynVector) {
+        self.zip_with(other, |a, b| *a -= b);
+    }
+}
+
+impl Neg for &DynVector {
+    type Output = DynVector;
+
+    fn neg(self) -> DynVector {
+        self.map(|a| -a)
+    }
+}
+
+impl Mul<i64> for &DynVector {
+    type Output = DynVector;
+
+    fn mul(self, factor: i64) -> DynVector {
+        self.map(|a| a * factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let a = Vector::new([1, -2, 3]);
+        let b = Vector::new([4, 0, -1]);
+        assert_eq!(a + b, Vector::new([5, -2, 2]));
+        assert_eq!(a - b, Vector::new([-3, -2, 4]));
+        assert_eq!(-a * 2, Vector::new([-2, 4, -6]));
+        assert_eq!((b - a).signum(), Vector::new([1, 1, -1]));
+        assert_eq!(a.norm(), 6);
+        assert_eq!(a.manhattan(b), 9);
+
+        let mut c = Vector3::default();
+        c += a;
+        c[2] = 0;
+        c -= b;
+        assert_eq!(c, Vector::new([-3, -2, 1]));
+        assert_eq!(format!("{:?}", c), "<-3, -2, 1>");
+    }
+
+    #[test]
+    fn test_project() {
+        let a = Vector::new([7, 8, 9, 10]);
+        assert_eq!(a.project(2), Vector::new([9]));
+        assert_eq!(a.axes().len(), 4);
+    }
+
+    #[test]
+    fn test_dyn_vector() {
+        let a = DynVector::new(vec![1, -2, 3, 0]);
+        let b = DynVector::from(Vector::new([4, 0, -1, 2]));
+        assert_eq!(&a + &b, DynVector::new(vec![5, -2, 2, 2]));
+        assert_eq!(&(-&a) * 2, DynVector::new(vec![-2, 4, -6, 0]));
+        assert_eq!((&b - &a).signum(), DynVector::new(vec![1, 1, -1, 1]));
+        assert_eq!((a.norm(), a.manhattan(&b)), (6, 11));
+        assert_eq!(a.project(1), Vector::new([-2]));
+
+        let mut c = DynVector::zero(4);
+        c += &a;
+        c[2] = 0;
+        c -= &b;
+        assert_eq!(c, DynVector::new(vec![-3, -2, 1, -2]));
+        assert_eq!(format!("{:?}", c), "<-3, -2, 1, -2>");
+        assert_eq!(c.dimensions(), c.axes().len());
+    }
+}
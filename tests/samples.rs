@@ -0,0 +1,40 @@
+use aoc2019::samples;
+use aoc2019::Result;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Day binaries are built next to the directory holding this test.
+fn day_binary(day: u32) -> Result<PathBuf> {
+    let exe = env::current_exe()?;
+    let dir = exe
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new("."));
+    Ok(dir.join(format!("day{:02}{}", day, env::consts::EXE_SUFFIX)))
+}
+
+#[test]
+fn test_samples() -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let days = samples::days(root)?;
+    assert!(!days.is_empty());
+
+    let mut failures = vec![];
+    for day in days {
+        let output = Command::new(day_binary(day)?)
+            .arg("--samples")
+            .env("CARGO_MANIFEST_DIR", root)
+            .output()?;
+        if !output.status.success() {
+            failures.push(format!(
+                "day {}:\n{}{}",
+                day,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    Ok(())
+}